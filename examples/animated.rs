@@ -0,0 +1,27 @@
+use rui::*;
+
+fn main() {
+    state(
+        || 0.0,
+        |x, _| {
+            vstack((
+                animated(x, Curve::spring(0.4), |x, _| {
+                    circle()
+                        .color(AZURE_HIGHLIGHT)
+                        .size([50.0, 50.0])
+                        .offset([x, 0.0])
+                }),
+                animated(x, Curve::ease_in_out(0.5), |x, _| {
+                    rectangle()
+                        .corner_radius(5.0)
+                        .color(RED_HIGHLIGHT.lerp(&AZURE_HIGHLIGHT, x / 200.0))
+                        .size([50.0, 50.0])
+                        .offset([x, 0.0])
+                }),
+                button("move", move |cx| cx[x] = 200.0 - cx[x]),
+            ))
+            .padding(Auto)
+        },
+    )
+    .run()
+}
@@ -0,0 +1,34 @@
+use rui::*;
+
+// Press Cmd+Shift+P (Ctrl+Shift+P off macOS) to search the commands.
+
+fn main() {
+    state(
+        || 0,
+        |count, _| {
+            state(
+                || false,
+                move |open, cx| {
+                    zstack((
+                        text(&format!("count: {}", cx[count]))
+                            .padding(Auto)
+                            .command("Count:Increment", "Primary+Up".parse().ok(), move |cx| {
+                                cx[count] += 1
+                            })
+                            .command("Count:Decrement", "Primary+Down".parse().ok(), move |cx| {
+                                cx[count] -= 1
+                            })
+                            .command("Count:Reset", None, move |cx| cx[count] = 0),
+                        command_palette(open),
+                    ))
+                    .command(
+                        "View:Command Palette",
+                        "Primary+Shift+P".parse().ok(),
+                        move |cx| cx[open] = true,
+                    )
+                },
+            )
+        },
+    )
+    .run()
+}
@@ -1,11 +1,12 @@
 use rui::*;
 
-fn anim_to(current: &mut LocalOffset, target: LocalOffset) -> bool {
+fn anim_to(current: &mut LocalOffset, target: LocalOffset, dt: f32) -> bool {
     if *current != target {
         if (*current - target).length() < 0.01 {
             *current = target;
         } else {
-            *current = current.lerp(target, 0.05);
+            // Close 95% of the distance every second.
+            *current = current.lerp(target, 1.0 - 0.05f32.powf(dt));
         }
         true
     } else {
@@ -34,10 +35,11 @@ fn main() {
                             cx[off] = LocalOffset::zero();
                         }
                     })
-                    .anim(move |cx, _dt| {
+                    .anim(move |cx, dt| {
                         let mut v = cx[anim_off];
-                        if anim_to(&mut v, cx[off]) {
+                        if anim_to(&mut v, cx[off], dt) {
                             cx[anim_off] = v;
+                            cx.request_animation_frame();
                         }
                     })
                     .padding(Auto)
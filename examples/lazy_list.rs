@@ -0,0 +1,8 @@
+use rui::*;
+
+fn main() {
+    lazy_list(50_000, 24.0, |i| {
+        hstack((circle().size([12.0, 12.0]), format!("row {}", i)))
+    })
+    .run()
+}
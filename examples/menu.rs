@@ -1,25 +1,41 @@
 use rui::*;
 
-// Requires tao instead of winit for menus.
-// Run with: cargo run --example menu --no-default-features --features tao
+// Prints the menu each time it changes. An app would build native
+// menus here and send the chosen command with `Context::send_command`.
 
 fn main() {
     hstack((
-        circle()
-            .color(RED_HIGHLIGHT)
-            .padding(Auto)
-            .command("File:New", Some(HotKey::KeyN), |_| println!("new")),
+        circle().color(RED_HIGHLIGHT).padding(Auto).command(
+            "File:New",
+            "Primary+N".parse().ok(),
+            |_| println!("new"),
+        ),
         rectangle()
             .corner_radius(5.0)
             .color(AZURE_HIGHLIGHT)
             .padding(Auto)
-            .command("Edit:Two", None, |_| println!("two"))
+            .command("Edit:Two", "Primary+Shift+2".parse().ok(), |_| {
+                println!("two")
+            })
             .command("Edit:Three", None, |_| println!("three"))
+            .enabled(false)
             .command("Custom:Submenu:One", None, |_| println!("submenu one"))
             .command("Custom:Submenu:Two", None, |_| println!("submenu two"))
             .command_group((command("Custom 2:Four")
                 .action(|| println!("four"))
                 .hotkey(HotKey::KeyF),)),
     ))
-    .run()
+    .run_with_commands(|commands| {
+        println!("menu:");
+        for cmd in commands {
+            let check = match cmd.checked {
+                Some(true) => "[x] ",
+                Some(false) => "[ ] ",
+                None => "",
+            };
+            let key = cmd.key.map(|key| format!(" ({})", key)).unwrap_or_default();
+            let disabled = if cmd.enabled { "" } else { " (disabled)" };
+            println!("  {}{}{}{}", check, cmd.path, key, disabled);
+        }
+    })
 }
@@ -0,0 +1,35 @@
+use futures::channel::mpsc;
+use rui::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+fn main() {
+    // Stand-in for an audio thread sending levels much faster than
+    // the display refreshes.
+    let (send, recv) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let mut phase = 0.0f32;
+        loop {
+            phase += 0.01;
+            if send.unbounded_send(0.5 + 0.5 * phase.sin()).is_err() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    });
+    let recv = Rc::new(RefCell::new(recv));
+
+    state(
+        || 0.0,
+        move |level, cx| {
+            rectangle()
+                .color(AZURE_HIGHLIGHT)
+                .size([40.0, 200.0 * cx[level]])
+                .on_receive(recv.clone(), move |cx, value| cx[level] = value)
+                .coalesce()
+                .padding(Auto)
+        },
+    )
+    .run()
+}
@@ -7,7 +7,7 @@ fn main() {
 
         let paint = vger.color_paint(vger::Color::MAGENTA.alpha(0.2));
 
-        vger.translate([0.0, rect.height()]);
+        vger.translate([0.0, rect.height()].into());
 
         let font_size = 24;
         let break_width = Some(rect.width());
@@ -0,0 +1,14 @@
+use rui::*;
+use std::time::Duration;
+
+fn main() {
+    state(
+        || 0,
+        |seconds, cx| {
+            text(&format!("{} seconds", cx[seconds]))
+                .timer(Duration::from_secs(1), move |cx| cx[seconds] += 1)
+                .padding(Auto)
+        },
+    )
+    .run()
+}
@@ -23,6 +23,7 @@ fn todo_list(todos: impl Binding<Vec<String>>) -> impl View {
             let id = *id;
             with_cx(move |cx| todos.with(cx, |todos| todos[id].clone()))
         })
+        .transition(Transition::slide(Edge::Leading))
     })
 }
 
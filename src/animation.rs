@@ -0,0 +1,408 @@
+use crate::*;
+
+/// Values which `animated` can interpolate. Springs move values as
+/// vectors, keeping track of their velocity.
+pub trait Animatable: Clone + PartialEq + 'static {
+    /// Blends from `self` (at 0) to `other` (at 1).
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+
+    /// Returns `self * a + other * b`.
+    fn combine(&self, a: f32, other: &Self, b: f32) -> Self;
+
+    /// Length of the value as a vector.
+    fn magnitude(&self) -> f32;
+}
+
+impl Animatable for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    fn combine(&self, a: f32, other: &Self, b: f32) -> Self {
+        self * a + other * b
+    }
+
+    fn magnitude(&self) -> f32 {
+        self.abs()
+    }
+}
+
+impl Animatable for LocalPoint {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        LocalPoint::lerp(*self, *other, t)
+    }
+
+    fn combine(&self, a: f32, other: &Self, b: f32) -> Self {
+        LocalPoint::new(self.x * a + other.x * b, self.y * a + other.y * b)
+    }
+
+    fn magnitude(&self) -> f32 {
+        self.to_vector().length()
+    }
+}
+
+impl Animatable for LocalOffset {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        LocalOffset::lerp(*self, *other, t)
+    }
+
+    fn combine(&self, a: f32, other: &Self, b: f32) -> Self {
+        *self * a + *other * b
+    }
+
+    fn magnitude(&self) -> f32 {
+        self.length()
+    }
+}
+
+impl Animatable for LocalSize {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        LocalSize::lerp(*self, *other, t)
+    }
+
+    fn combine(&self, a: f32, other: &Self, b: f32) -> Self {
+        LocalSize::new(
+            self.width * a + other.width * b,
+            self.height * a + other.height * b,
+        )
+    }
+
+    fn magnitude(&self) -> f32 {
+        self.width.hypot(self.height)
+    }
+}
+
+impl Animatable for Color {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Color::new(
+            Animatable::lerp(&self.r, &other.r, t),
+            Animatable::lerp(&self.g, &other.g, t),
+            Animatable::lerp(&self.b, &other.b, t),
+            Animatable::lerp(&self.a, &other.a, t),
+        )
+    }
+
+    fn combine(&self, a: f32, other: &Self, b: f32) -> Self {
+        Color::new(
+            self.r * a + other.r * b,
+            self.g * a + other.g * b,
+            self.b * a + other.b * b,
+            self.a * a + other.a * b,
+        )
+    }
+
+    fn magnitude(&self) -> f32 {
+        (self.r * self.r + self.g * self.g + self.b * self.b + self.a * self.a).sqrt()
+    }
+}
+
+/// A solid color as a gradient between the same color, placed like `like`.
+fn color_gradient(color: Color, like: &Paint) -> Paint {
+    match like {
+        Paint::Gradient { start, end, .. } => Paint::Gradient {
+            start: *start,
+            end: *end,
+            inner_color: color,
+            outer_color: color,
+        },
+        Paint::Color(_) => Paint::Color(color),
+    }
+}
+
+impl Animatable for Paint {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Paint::Color(a), Paint::Color(b)) => Paint::Color(a.lerp(b, t)),
+            (
+                Paint::Gradient {
+                    start,
+                    end,
+                    inner_color,
+                    outer_color,
+                },
+                Paint::Gradient {
+                    start: start2,
+                    end: end2,
+                    inner_color: inner_color2,
+                    outer_color: outer_color2,
+                },
+            ) => Paint::Gradient {
+                start: start.lerp(start2, t),
+                end: end.lerp(end2, t),
+                inner_color: inner_color.lerp(inner_color2, t),
+                outer_color: outer_color.lerp(outer_color2, t),
+            },
+            (Paint::Color(color), Paint::Gradient { .. }) => {
+                color_gradient(*color, other).lerp(other, t)
+            }
+            (Paint::Gradient { .. }, Paint::Color(color)) => {
+                self.lerp(&color_gradient(*color, self), t)
+            }
+        }
+    }
+
+    fn combine(&self, a: f32, other: &Self, b: f32) -> Self {
+        match (self, other) {
+            (Paint::Color(x), Paint::Color(y)) => Paint::Color(x.combine(a, y, b)),
+            (
+                Paint::Gradient {
+                    start,
+                    end,
+                    inner_color,
+                    outer_color,
+                },
+                Paint::Gradient {
+                    start: start2,
+                    end: end2,
+                    inner_color: inner_color2,
+                    outer_color: outer_color2,
+                },
+            ) => Paint::Gradient {
+                start: start.combine(a, start2, b),
+                end: end.combine(a, end2, b),
+                inner_color: inner_color.combine(a, inner_color2, b),
+                outer_color: outer_color.combine(a, outer_color2, b),
+            },
+            (Paint::Color(color), Paint::Gradient { .. }) => {
+                color_gradient(*color, other).combine(a, other, b)
+            }
+            (Paint::Gradient { .. }, Paint::Color(color)) => {
+                self.combine(a, &color_gradient(*color, self), b)
+            }
+        }
+    }
+
+    fn magnitude(&self) -> f32 {
+        match self {
+            Paint::Color(color) => color.magnitude(),
+            Paint::Gradient {
+                start,
+                end,
+                inner_color,
+                outer_color,
+            } => [
+                start.magnitude(),
+                end.magnitude(),
+                inner_color.magnitude(),
+                outer_color.magnitude(),
+            ]
+            .iter()
+            .map(|m| m * m)
+            .sum::<f32>()
+            .sqrt(),
+        }
+    }
+}
+
+/// How an animated value moves from its old value to its new one.
+/// Durations are in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Curve {
+    /// Constant speed.
+    Linear { duration: f32 },
+
+    /// Speeds up then slows down.
+    EaseInOut { duration: f32 },
+
+    /// Follows a CSS-style cubic bezier from (0, 0) to (1, 1) with
+    /// control points (x1, y1) and (x2, y2).
+    CubicBezier {
+        duration: f32,
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+    },
+
+    /// Critically damped spring: as fast as possible without overshooting.
+    /// `response` is the period of the spring if it were undamped.
+    ///
+    /// `animated` keeps the spring's velocity when the value changes
+    /// in the middle of an animation. `duration` and `progress`
+    /// describe a spring which starts at rest.
+    Spring { response: f32 },
+}
+
+/// Value of ωt at which a critically damped spring has settled to
+/// within 0.1% of its target, solving (1 + ωt)e^(-ωt) = 0.001.
+const SPRING_SETTLE: f32 = 9.2334;
+
+impl Curve {
+    pub fn linear(duration: f32) -> Self {
+        Curve::Linear { duration }
+    }
+
+    pub fn ease_in_out(duration: f32) -> Self {
+        Curve::EaseInOut { duration }
+    }
+
+    pub fn cubic_bezier(duration: f32, p1: [f32; 2], p2: [f32; 2]) -> Self {
+        Curve::CubicBezier {
+            duration,
+            x1: p1[0],
+            y1: p1[1],
+            x2: p2[0],
+            y2: p2[1],
+        }
+    }
+
+    pub fn spring(response: f32) -> Self {
+        Curve::Spring { response }
+    }
+
+    /// Seconds until the value reaches its target.
+    pub fn duration(&self) -> f32 {
+        match *self {
+            Curve::Linear { duration }
+            | Curve::EaseInOut { duration }
+            | Curve::CubicBezier { duration, .. } => duration,
+            Curve::Spring { response } => SPRING_SETTLE * response / std::f32::consts::TAU,
+        }
+    }
+
+    /// How far along the value is after `elapsed` seconds, from 0 to 1.
+    pub fn progress(&self, elapsed: f32) -> f32 {
+        let duration = self.duration();
+        if elapsed >= duration || duration <= 0.0 {
+            return 1.0;
+        }
+        let t = (elapsed / duration).max(0.0);
+        match *self {
+            Curve::Linear { .. } => t,
+            Curve::EaseInOut { .. } => cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+            Curve::CubicBezier { x1, y1, x2, y2, .. } => cubic_bezier(x1, y1, x2, y2, t),
+            Curve::Spring { response } => {
+                let wt = elapsed * std::f32::consts::TAU / response;
+                1.0 - (1.0 + wt) * (-wt).exp()
+            }
+        }
+    }
+}
+
+/// Moves `value` towards `target` along a critically damped spring for
+/// `dt` seconds, returning the new value and velocity. The spring is
+/// solved exactly, so long frames don't throw it off.
+pub(crate) fn spring_step<T: Animatable>(
+    value: &T,
+    velocity: &T,
+    target: &T,
+    response: f32,
+    dt: f32,
+) -> (T, T) {
+    let omega = std::f32::consts::TAU / response;
+    let decay = (-omega * dt).exp();
+
+    // With y the distance from the target, y(t) = (y0 + ct)e^(-ωt)
+    // where c = v0 + ωy0.
+    let y0 = value.combine(1.0, target, -1.0);
+    let c = velocity.combine(1.0, &y0, omega);
+    let y = y0.combine(decay, &c, dt * decay);
+    let velocity = velocity.combine(decay, &c, -omega * dt * decay);
+
+    (
+        value.combine(1.0, &y.combine(1.0, &y0, -1.0), 1.0),
+        velocity,
+    )
+}
+
+/// One coordinate of a bezier from 0 to 1 with control points `a` and `b`.
+fn bezier(a: f32, b: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * a + 3.0 * r * s * s * b + s * s * s
+}
+
+/// Evaluates a CSS-style timing function: finds where the curve's x
+/// is `x` and returns its y there.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    // Bisection, since x increases along the curve when the
+    // control points' x coordinates are within [0, 1].
+    let mut lo = 0.0;
+    let mut hi = 1.0;
+    for _ in 0..24 {
+        let mid = (lo + hi) * 0.5;
+        if bezier(x1, x2, mid) < x {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    bezier(y1, y2, (lo + hi) * 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_curves() {
+        let linear = Curve::linear(2.0);
+        assert_eq!(linear.progress(0.0), 0.0);
+        assert_eq!(linear.progress(0.5), 0.25);
+        assert_eq!(linear.progress(3.0), 1.0);
+
+        let ease = Curve::ease_in_out(1.0);
+        assert!(ease.progress(0.1) < 0.1);
+        assert!((ease.progress(0.5) - 0.5).abs() < 0.001);
+        assert!(ease.progress(0.9) > 0.9);
+
+        // A bezier along the diagonal is linear.
+        let bezier = Curve::cubic_bezier(1.0, [0.25, 0.25], [0.75, 0.75]);
+        assert!((bezier.progress(0.3) - 0.3).abs() < 0.001);
+
+        let spring = Curve::spring(0.5);
+        let mut last = 0.0;
+        for i in 1..10 {
+            let p = spring.progress(i as f32 * 0.05);
+            assert!(p > last && p <= 1.0);
+            last = p;
+        }
+        assert!(spring.progress(spring.duration() - 0.001) > 0.998);
+        assert_eq!(spring.progress(spring.duration()), 1.0);
+    }
+
+    #[test]
+    fn test_spring() {
+        // One long step lands where many short ones do.
+        let (x, v) = spring_step(&0.0, &0.0, &1.0, 0.5, 0.25);
+        let (mut x2, mut v2) = (0.0, 0.0);
+        for _ in 0..25 {
+            (x2, v2) = spring_step(&x2, &v2, &1.0, 0.5, 0.01);
+        }
+        assert!((x - x2).abs() < 1e-4);
+        assert!((v - v2).abs() < 1e-3);
+
+        // From rest, it follows the curve.
+        assert!((x - Curve::spring(0.5).progress(0.25)).abs() < 1e-4);
+
+        // Moving away from the target, it keeps going for a while.
+        let (x, v) = spring_step(&0.0, &10.0, &0.0, 0.5, 0.01);
+        assert!(x > 0.0 && v > 0.0);
+    }
+
+    #[test]
+    fn test_lerp_paint() {
+        let a = Paint::Color(Color::new(0.0, 0.0, 0.0, 1.0));
+        let b = Paint::Color(Color::new(1.0, 0.5, 0.0, 1.0));
+        assert_eq!(
+            a.lerp(&b, 0.5),
+            Paint::Color(Color::new(0.5, 0.25, 0.0, 1.0))
+        );
+
+        let g = Paint::Gradient {
+            start: [0.0, 0.0].into(),
+            end: [10.0, 0.0].into(),
+            inner_color: Color::new(1.0, 1.0, 1.0, 1.0),
+            outer_color: Color::new(0.0, 0.0, 0.0, 1.0),
+        };
+        match a.lerp(&g, 0.5) {
+            Paint::Gradient {
+                end, inner_color, ..
+            } => {
+                assert_eq!(end, [10.0, 0.0].into());
+                assert_eq!(inner_color, Color::new(0.5, 0.5, 0.5, 1.0));
+            }
+            _ => panic!("expected a gradient"),
+        }
+    }
+}
@@ -108,8 +108,7 @@ mod tests {
     #[test]
     fn test_bind() {
         let mut cx = Context::new();
-        let id = ViewId::default();
-        cx.init_state(id, &MyState::default);
+        let id = cx.init_state(&IdPath::root(), &MyState::default);
         let s = StateHandle::new(id);
 
         let b = bind(s, MyLens {});
@@ -0,0 +1,67 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Source of time for animations.
+///
+/// `Context` uses a `SystemClock` by default. Tests can swap in a
+/// `ManualClock` with `Context::set_clock` so animations step by
+/// exact amounts.
+pub trait Clock {
+    /// Time elapsed since some fixed starting point.
+    fn now(&self) -> Duration;
+}
+
+/// Clock which follows real time.
+pub struct SystemClock {
+    start: instant::Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: instant::Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock which only moves when told to. Clones share the same time,
+/// so keep a clone to advance the clock after passing it to the `Context`.
+///
+/// ```
+/// # use rui::*;
+/// # use std::time::Duration;
+/// let clock = ManualClock::default();
+/// let mut cx = Context::new();
+/// cx.set_clock(clock.clone());
+/// clock.advance(Duration::from_millis(100));
+/// assert_eq!(cx.now(), Duration::from_millis(100));
+/// ```
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    time: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        self.time.set(self.time.get() + duration);
+    }
+
+    /// Sets the current time.
+    pub fn set(&self, time: Duration) {
+        self.time.set(time);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.time.get()
+    }
+}
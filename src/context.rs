@@ -3,8 +3,11 @@ use euclid::*;
 use std::any::Any;
 use std::any::TypeId;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::iter::FromIterator;
 use std::ops;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub type LocalSpace = vger::defs::LocalSpace;
 pub type WorldSpace = vger::defs::WorldSpace;
@@ -17,14 +20,23 @@ pub type WorldPoint = Point2D<f32, WorldSpace>;
 pub type LocalToWorld = Transform2D<f32, LocalSpace, WorldSpace>;
 pub type WorldToLocal = Transform2D<f32, WorldSpace, LocalSpace>;
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CommandInfo {
     pub path: String,
-    pub key: Option<HotKey>,
+    pub key: Option<Shortcut>,
+
+    /// Disabled commands are shown greyed out and don't run.
+    pub enabled: bool,
+
+    /// Whether a toggle is on, or `None` if the command isn't a toggle.
+    pub checked: Option<bool>,
 }
 
 pub const DEBUG_LAYOUT: bool = false;
 
+/// How long to wait for the next frame while animating.
+pub(crate) const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
 #[derive(Copy, Clone, Default, PartialEq, Debug)]
 pub(crate) struct LayoutBox {
     pub rect: LocalRect,
@@ -34,12 +46,22 @@ pub(crate) struct LayoutBox {
 pub(crate) struct StateHolder {
     pub state: Box<dyn Any>,
     pub dirty: bool,
+
+    /// Where the state is in the view tree.
+    pub path: IdPath,
 }
 
 pub(crate) type StateMap = HashMap<ViewId, StateHolder>;
 
 pub(crate) type EnvMap = HashMap<TypeId, Box<dyn Any>>;
 
+/// Copy of the window's contents, so `render` only needs to repaint
+/// what changed.
+struct BackBuffer {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
 pub struct RenderInfo<'a> {
     pub device: &'a wgpu::Device,
     pub surface: &'a wgpu::Surface,
@@ -51,13 +73,26 @@ pub struct RenderInfo<'a> {
 /// shouldn't have to interact with it directly.
 pub struct Context {
     /// Layout information for all views.
-    layout: HashMap<IdPath, LayoutBox>,
+    layout: HashMap<ViewId, LayoutBox>,
+
+    /// Proposed and resulting sizes from the last layout of each container.
+    layout_cache: HashMap<ViewId, (LocalSize, LocalSize)>,
+
+    /// States which changed since the last layout. Cached layouts
+    /// below these are computed again.
+    dirty_states: HashSet<ViewId>,
+
+    /// Views on the paths to changed states. Cached layouts of
+    /// these are computed again.
+    dirty_ancestors: HashSet<ViewId>,
 
-    /// Allocated ViewIds.
-    view_ids: HashMap<IdPath, ViewId>,
+    /// Views which read the environment during layout, and the views
+    /// around them. Environment values aren't tracked by state, so
+    /// these are always laid out again.
+    env_readers: HashSet<ViewId>,
 
-    /// Next allocated id.
-    next_id: ViewId,
+    /// Window size of the current layout, or `None` before the first.
+    layout_size: Option<Size2D<f32, WorldSpace>>,
 
     /// Which views each touch (or mouse pointer) is interacting with.
     pub(crate) touches: [ViewId; 16],
@@ -77,6 +112,13 @@ pub struct Context {
     /// The view that has the keyboard focus.
     pub(crate) focused_id: Option<ViewId>,
 
+    /// Has a scroll view moved for the current scroll event?
+    pub(crate) scroll_handled: bool,
+
+    /// Caret of the focused text view, as of the last draw, so input
+    /// methods can show their candidates next to it.
+    pub(crate) ime_rect: Option<WorldRect>,
+
     /// The current title of the window
     pub window_title: String,
 
@@ -98,6 +140,19 @@ pub struct Context {
     /// Regions of window that needs repainting.
     pub(crate) dirty_region: Region<WorldSpace>,
 
+    /// Repaint the whole window next frame, rather than just `dirty_region`.
+    pub(crate) repaint_all: bool,
+
+    /// Has an animation added to `dirty_region` during this update?
+    animation_repaint: bool,
+
+    /// The part of the window being repainted, or `None` when
+    /// repainting everything.
+    pub(crate) repaint_rect: Option<WorldRect>,
+
+    /// Last frame drawn by `render`.
+    back_buffer: Option<BackBuffer>,
+
     /// State dependencies.
     pub(crate) deps: HashMap<ViewId, Vec<ViewId>>,
 
@@ -108,7 +163,7 @@ pub struct Context {
     window_size: Size2D<f32, WorldSpace>,
 
     /// Offset for events at the root level.
-    root_offset: LocalOffset,
+    pub(crate) root_offset: LocalOffset,
 
     /// Render the dirty rectangle for debugging?
     render_dirty: bool,
@@ -120,6 +175,54 @@ pub struct Context {
 
     /// Value of grab_cursor before processing event.
     pub(crate) prev_grab_cursor: bool,
+
+    /// Source of time for animations and timers.
+    clock: Box<dyn Clock>,
+
+    /// Clock time of the last animation frame.
+    last_frame: Duration,
+
+    /// Seconds since the previous animation frame.
+    frame_delta: f32,
+
+    /// Has a view asked for another animation frame?
+    animation_requested: bool,
+
+    /// Timers started with `after`, `every` and the `timer` modifier.
+    pub(crate) timers: HashMap<u64, Timer>,
+
+    /// Id for the next timer.
+    next_timer_id: u64,
+
+    /// Futures started with `spawn` and the `task` modifier.
+    pub(crate) tasks: HashMap<u64, Task>,
+
+    /// Id for the next task.
+    next_task_id: u64,
+
+    /// Tasks which were woken since they were last polled.
+    ready_tasks: ReadyTasks,
+
+    /// Wakes the event loop so woken tasks get polled.
+    wake: WakeFn,
+
+    /// Menu commands declared by the view tree as of the last update.
+    declared_commands: Vec<CommandInfo>,
+
+    /// User changes to command shortcuts.
+    keymap: Keymap,
+
+    /// `declared_commands` with the keymap applied.
+    menu_commands: Vec<CommandInfo>,
+
+    /// Shortcuts shared by more than one of `menu_commands`.
+    keymap_conflicts: Vec<KeymapConflict>,
+
+    /// Have the menu commands changed since the host last asked?
+    pub(crate) menu_commands_changed: bool,
+
+    /// Commands queued by `send_command`.
+    pending_commands: Vec<String>,
 }
 
 impl Default for Context {
@@ -132,14 +235,19 @@ impl Context {
     pub fn new() -> Self {
         Self {
             layout: HashMap::new(),
-            view_ids: HashMap::new(),
-            next_id: ViewId { id: 0 },
+            layout_cache: HashMap::new(),
+            dirty_states: HashSet::new(),
+            dirty_ancestors: HashSet::new(),
+            env_readers: HashSet::new(),
+            layout_size: None,
             touches: [ViewId::default(); 16],
             starts: [LocalPoint::zero(); 16],
             previous_position: [LocalPoint::zero(); 16],
             mouse_button: None,
             key_mods: Default::default(),
             focused_id: None,
+            scroll_handled: false,
+            ime_rect: None,
             window_title: "rui".into(),
             fullscreen: false,
             state_map: HashMap::new(),
@@ -147,6 +255,10 @@ impl Context {
             enable_dirty: true,
             env: HashMap::new(),
             dirty_region: Region::EMPTY,
+            animation_repaint: false,
+            repaint_all: true,
+            repaint_rect: None,
+            back_buffer: None,
             deps: HashMap::new(),
             id_stack: vec![],
             window_size: Size2D::default(),
@@ -155,6 +267,22 @@ impl Context {
             access_node_classes: accesskit::NodeClassSet::default(),
             grab_cursor: false,
             prev_grab_cursor: false,
+            clock: Box::<SystemClock>::default(),
+            last_frame: Duration::ZERO,
+            frame_delta: 0.0,
+            animation_requested: false,
+            timers: HashMap::new(),
+            next_timer_id: 0,
+            tasks: HashMap::new(),
+            next_task_id: 0,
+            ready_tasks: Default::default(),
+            wake: Arc::new(|| ()),
+            declared_commands: vec![],
+            keymap: Keymap::new(),
+            menu_commands: vec![],
+            keymap_conflicts: vec![],
+            menu_commands_changed: false,
+            pending_commands: vec![],
         }
     }
 
@@ -165,17 +293,47 @@ impl Context {
         vger: &mut Vger,
         access_nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
         window_size: Size2D<f32, WorldSpace>,
+    ) -> bool {
+        self.update_with(view, vger, access_nodes, window_size)
+    }
+
+    /// Version of `update` which measures text without vger.
+    pub(crate) fn update_with(
+        &mut self,
+        view: &impl View,
+        text: &mut dyn TextMeasurer,
+        access_nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+        window_size: Size2D<f32, WorldSpace>,
     ) -> bool {
         // If the window size has changed, force a relayout.
         if window_size != self.window_size {
-            self.deps.clear();
+            self.invalidate_layout();
             self.window_size = window_size;
+            self.repaint_all = true;
         }
 
-        let mut path = vec![0];
+        let mut path = IdPath::root();
+
+        self.poll_tasks();
+        self.run_timers();
+
+        for name in std::mem::take(&mut self.pending_commands) {
+            self.process(view, &Event::Command(name));
+        }
+
+        // Run any animations. The first frame after being idle counts
+        // as one frame, so `anim` closures which don't ask for frames
+        // still move a step on each update.
+        let now = self.clock.now();
+        self.frame_delta = if std::mem::take(&mut self.animation_requested) {
+            now.saturating_sub(self.last_frame).as_secs_f32()
+        } else {
+            FRAME_INTERVAL.as_secs_f32()
+        };
+        self.last_frame = now;
 
-        // Run any animations.
         let mut actions = vec![];
+        self.animation_repaint = false;
         view.process(&Event::Anim, &mut path, self, &mut actions);
         assert!(path.len() == 1);
 
@@ -187,9 +345,25 @@ impl Context {
             let keep_set = HashSet::<ViewId>::from_iter(keep);
             self.state_map.retain(|k, _| keep_set.contains(k));
 
-            let mut new_layout = self.layout.clone();
-            new_layout.retain(|k, _| keep_set.contains(&self.view_id(k)));
-            self.layout = new_layout;
+            self.layout.retain(|k, _| keep_set.contains(k));
+            self.layout_cache.retain(|k, _| keep_set.contains(k));
+            self.env_readers.retain(|k| keep_set.contains(k));
+            self.timers.retain(|_, timer| match timer.owner {
+                Some(owner) => keep_set.contains(&owner),
+                None => true,
+            });
+            self.tasks.retain(|_, task| match task.owner {
+                Some(owner) => keep_set.contains(&owner),
+                None => true,
+            });
+
+            // Menus need updating when commands come and go.
+            let mut cmds = vec![];
+            self.commands(view, &mut cmds);
+            if cmds != self.declared_commands {
+                self.declared_commands = cmds;
+                self.apply_keymap();
+            }
 
             // Get a new accesskit tree.
             let mut nodes = vec![];
@@ -212,29 +386,40 @@ impl Context {
                 // println!("access nodes unchanged");
             }
 
-            // XXX: we're doing layout both here and in rendering.
-            view.layout(
-                &mut path,
-                &mut LayoutArgs {
-                    sz: [window_size.width, window_size.height].into(),
-                    cx: self,
-                    text_bounds: &mut |str, size, max_width| vger.text_bounds(str, size, max_width),
-                },
-            );
-            assert_eq!(path.len(), 1);
+            let old_layout = self.layout.clone();
+            self.layout_root(view, text, window_size);
+
+            // Views which moved or changed size leave behind
+            // pixels outside the dirty region.
+            if old_layout
+                .iter()
+                .any(|(path, layout_box)| self.layout.get(path) != Some(layout_box))
+            {
+                self.repaint_all = true;
+            }
 
             // Get dirty rectangles.
-            view.dirty(&mut path, LocalToWorld::identity(), self);
+            let xform = LocalToWorld::translation(self.root_offset.x, self.root_offset.y);
+            view.dirty(&mut path, xform, self);
 
             self.clear_dirty();
 
             true
         } else {
-            false
+            // Nothing changed, but the window might have been resized,
+            // or a transition might need repainting.
+            self.enable_dirty = false;
+            let laid_out = self.layout_if_needed(view, text, window_size);
+            self.enable_dirty = true;
+            laid_out || self.animation_repaint
         }
     }
 
     /// Redraw the UI using wgpu.
+    ///
+    /// The last frame is kept in a texture, so only the dirty region is
+    /// repainted. This needs the surface to allow `TextureUsages::COPY_DST`.
+    /// Otherwise, everything is redrawn.
     pub fn render(
         &mut self,
         render_info: RenderInfo,
@@ -256,35 +441,148 @@ impl Context {
             }
         };
 
+        // Without a copy of the last frame, everything is drawn.
+        if !config.usage.contains(wgpu::TextureUsages::COPY_DST) {
+            let texture_view = frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            self.render_to_texture(
+                view,
+                vger,
+                &texture_view,
+                wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                window_size,
+                scale,
+            );
+            frame.present();
+            return;
+        }
+
+        let size = frame.texture.size();
+        let back_buffer = match self.back_buffer.take() {
+            Some(back_buffer)
+                if back_buffer.texture.size() == size
+                    && back_buffer.texture.format() == config.format =>
+            {
+                back_buffer
+            }
+            _ => {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("rui back buffer"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: config.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                self.repaint_all = true;
+                BackBuffer { texture, view }
+            }
+        };
+
+        self.draw_to_texture(
+            view,
+            vger,
+            &back_buffer.view,
+            wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+            window_size,
+            scale,
+        );
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("rui present"),
+        });
+        encoder.copy_texture_to_texture(
+            back_buffer.texture.as_image_copy(),
+            frame.texture.as_image_copy(),
+            size,
+        );
+        render_info.queue.submit(Some(encoder.finish()));
+        self.back_buffer = Some(back_buffer);
+
+        frame.present();
+    }
+
+    /// Redraw the UI into a texture without presenting it, for example to
+    /// draw on top of an existing scene. Use `wgpu::LoadOp::Load` to keep
+    /// the texture's contents.
+    ///
+    /// Everything is drawn each time, since the texture may hold a
+    /// different scene every frame. Use `render_changes_to_texture` for
+    /// a texture which only rui draws into.
+    pub fn render_to_texture(
+        &mut self,
+        view: &impl View,
+        vger: &mut Vger,
+        texture_view: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+        window_size: Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        // We don't know what's in the texture, so draw everything.
+        self.repaint_all = true;
+        self.draw_to_texture(view, vger, texture_view, load, window_size, scale);
+    }
+
+    /// Redraw only what changed since the last frame into a texture
+    /// which still holds that frame, such as one kept for the UI and
+    /// composited by the caller. `load` is used when everything is
+    /// drawn, as on the first frame. Use `render_to_texture` once after
+    /// replacing the texture.
+    pub fn render_changes_to_texture(
+        &mut self,
+        view: &impl View,
+        vger: &mut Vger,
+        texture_view: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+        window_size: Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        self.draw_to_texture(view, vger, texture_view, load, window_size, scale);
+    }
+
+    /// Draws what changed since the last frame into a texture, which
+    /// must still hold the last frame. `load` is used when drawing
+    /// everything.
+    fn draw_to_texture(
+        &mut self,
+        view: &impl View,
+        vger: &mut Vger,
+        texture_view: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+        window_size: Size2D<f32, WorldSpace>,
+        scale: f32,
+    ) {
+        let load = if self.repaint_all {
+            load
+        } else {
+            wgpu::LoadOp::Load
+        };
+
         vger.begin(window_size.width, window_size.height, scale);
 
-        let mut path = vec![0];
         // Disable dirtying the state during layout and rendering
         // to avoid constantly re-rendering if some state is saved.
         self.enable_dirty = false;
-        let local_window_size = window_size.cast_unit::<LocalSpace>();
-        let sz = view.layout(
-            &mut path,
-            &mut LayoutArgs {
-                sz: local_window_size,
-                cx: self,
-                text_bounds: &mut |str, size, max_width| vger.text_bounds(str, size, max_width),
-            },
-        );
-        assert!(path.len() == 1);
+        self.layout_if_needed(view, vger, window_size);
 
-        // Center the root view in the window.
-        self.root_offset = ((local_window_size - sz) / 2.0).into();
-
-        vger.translate(self.root_offset);
-        view.draw(&mut path, &mut DrawArgs { cx: self, vger });
+        let mut renderer = VgerRenderer::new(vger);
+        let dirty_rects = self.dirty_region.rects().to_vec();
+        let drawn = self.draw_dirty(view, &mut renderer);
         self.enable_dirty = true;
 
+        if !drawn {
+            return;
+        }
+
         if self.render_dirty {
-            let paint = vger.color_paint(RED_HIGHLIGHT);
+            let paint = renderer.color_paint(RED_HIGHLIGHT);
             let xf = WorldToLocal::identity();
-            for rect in self.dirty_region.rects() {
-                vger.stroke_rect(
+            for rect in &dirty_rects {
+                renderer.stroke_rect(
                     xf.transform_point(rect.min()),
                     xf.transform_point(rect.max()),
                     0.0,
@@ -294,18 +592,12 @@ impl Context {
             }
         }
 
-        self.dirty_region.clear();
-
-        let texture_view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
         let desc = wgpu::RenderPassDescriptor {
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &texture_view,
+                view: texture_view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    load,
                     store: wgpu::StoreOp::Store,
                 },
             })],
@@ -313,20 +605,176 @@ impl Context {
         };
 
         vger.encode(&desc);
+    }
 
-        frame.present();
+    /// Draws the root view, which must already be laid out.
+    pub(crate) fn draw_root(&mut self, view: &impl View, renderer: &mut dyn Renderer) {
+        let mut path = IdPath::root();
+        renderer.save();
+        renderer.translate(self.root_offset);
+        view.draw(&mut path, &mut DrawArgs { cx: self, renderer });
+        renderer.restore();
+    }
+
+    /// Draws the parts of the root view in the dirty region, or everything
+    /// if `repaint_all` is set, then clears both. The root view must
+    /// already be laid out.
+    ///
+    /// Returns false if nothing needed drawing.
+    pub(crate) fn draw_dirty(&mut self, view: &impl View, renderer: &mut dyn Renderer) -> bool {
+        let drawn = if self.repaint_all {
+            // Only a focused text view sets this, and losing focus
+            // repaints everything.
+            self.ime_rect = None;
+            self.draw_root(view, renderer);
+            true
+        } else if self.dirty_region.is_empty() {
+            false
+        } else {
+            let background = renderer.color_paint(BLACK);
+            for rect in self.dirty_region.rects().to_vec() {
+                // Whole points, so edges aren't blended with the last frame.
+                let rect = rect.round_out();
+                let local_rect = rect.cast_unit();
+
+                renderer.save();
+                renderer.scissor(local_rect);
+                renderer.fill_rect(local_rect.inflate(1.0, 1.0), 0.0, background);
+                self.repaint_rect = Some(rect);
+                self.draw_root(view, renderer);
+                renderer.restore();
+            }
+            self.repaint_rect = None;
+            true
+        };
+
+        self.repaint_all = false;
+        self.dirty_region.clear();
+        drawn
+    }
+
+    /// Lays out the root view unless the layout from `update` is
+    /// still good. Returns true if it laid out.
+    pub(crate) fn layout_if_needed(
+        &mut self,
+        view: &impl View,
+        text: &mut dyn TextMeasurer,
+        window_size: Size2D<f32, WorldSpace>,
+    ) -> bool {
+        if self.dirty || self.layout_size != Some(window_size) {
+            self.layout_root(view, text, window_size);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Lays out the root view and centers it in the window.
+    pub(crate) fn layout_root(
+        &mut self,
+        view: &impl View,
+        text: &mut dyn TextMeasurer,
+        window_size: Size2D<f32, WorldSpace>,
+    ) {
+        // Find the changed states, so cached layouts which
+        // might depend on them aren't used.
+        self.dirty_states.clear();
+        self.dirty_ancestors.clear();
+        for (id, holder) in &self.state_map {
+            if holder.dirty {
+                self.dirty_states.insert(*id);
+                self.dirty_ancestors.extend(holder.path.view_ids());
+            }
+        }
+
+        let mut path = IdPath::root();
+        let local_window_size = window_size.cast_unit::<LocalSpace>();
+        let sz = view.layout(
+            &mut path,
+            &mut LayoutArgs {
+                sz: local_window_size,
+                cx: self,
+                text_bounds: text,
+            },
+        );
+        assert!(path.len() == 1);
+
+        // Center the root view in the window.
+        self.root_offset = ((local_window_size - sz) / 2.0).into();
+        self.layout_size = Some(window_size);
+    }
+
+    /// Returns the size from the last layout of a container, if it was
+    /// proposed the same size, no state above or below it has changed and
+    /// it isn't affected by the environment.
+    pub(crate) fn cached_layout(
+        &mut self,
+        path: &IdPath,
+        proposed: LocalSize,
+    ) -> Option<LocalSize> {
+        let id = self.view_id(path);
+        if self.dirty_ancestors.contains(&id)
+            || self.reads_env(path)
+            || path.view_ids().any(|id| self.dirty_states.contains(&id))
+        {
+            return None;
+        }
+        match self.layout_cache.get(&id) {
+            Some((cached_proposed, size)) if *cached_proposed == proposed => Some(*size),
+            _ => None,
+        }
+    }
+
+    /// Remembers the size a container laid out to, for `cached_layout`.
+    pub(crate) fn cache_layout(&mut self, path: &IdPath, proposed: LocalSize, size: LocalSize) {
+        let id = self.view_id(path);
+        if self.reads_env(path) {
+            self.layout_cache.remove(&id);
+        } else {
+            self.layout_cache.insert(id, (proposed, size));
+        }
+    }
+
+    /// Notes that the view at `path` reads the environment, so neither it,
+    /// the views around it nor the views inside it use cached layouts.
+    pub(crate) fn layout_reads_env(&mut self, path: &IdPath) {
+        self.env_readers.extend(path.view_ids());
+    }
+
+    /// Does the layout of the view at `path` depend on the environment?
+    /// True inside and around views which read it.
+    pub(crate) fn reads_env(&self, path: &IdPath) -> bool {
+        path.view_ids().any(|id| self.env_readers.contains(&id))
+    }
+
+    /// Moves the keyboard focus. How views look with focus isn't
+    /// tracked by state, so everything is laid out and drawn again.
+    pub(crate) fn set_focus(&mut self, id: Option<ViewId>) {
+        if self.focused_id != id {
+            self.focused_id = id;
+            self.repaint_all = true;
+            self.invalidate_layout();
+            self.set_dirty();
+        }
+    }
+
+    /// Forgets cached layouts, so everything is laid out on the next update.
+    ///
+    /// Changes to state are tracked, but call this when layout depends on
+    /// something else, such as data shared with the rest of the app
+    /// through an `Rc`.
+    pub fn invalidate_layout(&mut self) {
+        self.layout_cache.clear();
+        self.deps.clear();
+        self.layout_size = None;
     }
 
     /// Process a UI event.
+    ///
+    /// Key presses matching a command's hotkey are sent as `Event::Command`.
     pub fn process(&mut self, view: &impl View, event: &Event) {
         let mut actions = vec![];
-        let mut path = vec![0];
-        view.process(
-            &event.offset(-self.root_offset),
-            &mut path,
-            self,
-            &mut actions,
-        );
+        self.process_with(view, event, &mut actions);
 
         for action in actions {
             if !action.is::<()>() {
@@ -335,53 +783,125 @@ impl Context {
         }
     }
 
+    /// Sends an event to the view tree, collecting the actions views
+    /// return. Shared with `TestHarness`.
+    pub(crate) fn process_with(
+        &mut self,
+        view: &impl View,
+        event: &Event,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Some(command) = self.hotkey_command(event) {
+            return self.process_with(view, &command, actions);
+        }
+
+        let mut path = IdPath::root();
+        self.scroll_handled = false;
+        view.process(&event.offset(-self.root_offset), &mut path, self, actions);
+    }
+
     /// Get menu commands.
     pub fn commands(&mut self, view: &impl View, cmds: &mut Vec<CommandInfo>) {
-        let mut path = vec![0];
+        let mut path = IdPath::root();
         view.commands(&mut path, self, cmds);
     }
 
-    pub(crate) fn view_id(&mut self, path: &IdPath) -> ViewId {
-        match self.view_ids.get_mut(path) {
-            Some(id) => *id,
-            None => {
-                let id = self.next_id;
-                self.view_ids.insert(path.clone(), id);
-                self.next_id.id += 1;
-                id
-            }
+    /// Menu commands as of the last update, with the keymap applied.
+    pub fn menu_commands(&self) -> &[CommandInfo] {
+        &self.menu_commands
+    }
+
+    /// Menu commands with the shortcuts they were declared with.
+    pub fn declared_commands(&self) -> &[CommandInfo] {
+        &self.declared_commands
+    }
+
+    /// User changes to command shortcuts.
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Changes command shortcuts. The new shortcuts work right away.
+    ///
+    /// Returns the shortcuts which more than one command now shares.
+    /// Only the first of those commands runs when its shortcut is
+    /// pressed.
+    pub fn set_keymap(&mut self, keymap: Keymap) -> Vec<KeymapConflict> {
+        self.keymap = keymap;
+        self.apply_keymap();
+        self.keymap_conflicts.clone()
+    }
+
+    /// Shortcuts shared by more than one menu command as of the last
+    /// update, with the keymap applied. Commands declared after the
+    /// keymap was set can add to these.
+    pub fn keymap_conflicts(&self) -> &[KeymapConflict] {
+        &self.keymap_conflicts
+    }
+
+    fn apply_keymap(&mut self) {
+        let cmds = self.keymap.apply(&self.declared_commands);
+        if cmds != self.menu_commands {
+            self.menu_commands = cmds;
+            self.menu_commands_changed = true;
         }
+
+        self.keymap_conflicts = self.keymap.conflicts(&self.declared_commands);
+    }
+
+    /// Runs a menu command on the next update, as if it had been
+    /// chosen from a menu.
+    pub fn send_command(&mut self, name: &str) {
+        self.pending_commands.push(name.into());
+        (self.wake)();
+    }
+
+    /// The command to send instead of a key press which is a hotkey.
+    /// Disabled commands are skipped, so their keys reach the views or
+    /// another command with the same shortcut.
+    pub(crate) fn hotkey_command(&self, event: &Event) -> Option<Event> {
+        let key = match event {
+            Event::Key(key) => *key,
+            _ => return None,
+        };
+        self.menu_commands
+            .iter()
+            .filter(|cmd| cmd.enabled)
+            .find(|cmd| cmd.key.is_some_and(|s| s.matches(key, self.key_mods)))
+            .map(|cmd| Event::Command(cmd.path.clone()))
+    }
+
+    pub(crate) fn view_id(&self, path: &IdPath) -> ViewId {
+        path.view_id()
     }
 
     pub(crate) fn get_layout(&self, path: &IdPath) -> LayoutBox {
-        match self.layout.get(path) {
+        match self.layout.get(&path.view_id()) {
             Some(b) => *b,
             None => LayoutBox::default(),
         }
     }
 
     pub(crate) fn update_layout(&mut self, path: &IdPath, layout_box: LayoutBox) {
-        match self.layout.get_mut(path) {
-            Some(bref) => *bref = layout_box,
-            None => {
-                self.layout.insert(path.clone(), layout_box);
-            }
-        }
+        self.layout.insert(path.view_id(), layout_box);
     }
 
     pub(crate) fn set_layout_offset(&mut self, path: &IdPath, offset: LocalOffset) {
-        match self.layout.get_mut(path) {
-            Some(boxref) => boxref.offset = offset,
-            None => {
-                self.layout.insert(
-                    path.clone(),
-                    LayoutBox {
-                        rect: LocalRect::default(),
-                        offset: offset,
-                    },
-                );
-            }
-        }
+        self.layout.entry(path.view_id()).or_default().offset = offset;
+    }
+
+    /// Adds a rectangle, in the coordinates described by `xform`,
+    /// to the region to repaint.
+    pub(crate) fn add_dirty_rect(&mut self, rect: LocalRect, xform: LocalToWorld) {
+        self.dirty_region
+            .add_rect(xform.outer_transformed_rect(&rect));
+    }
+
+    /// Repaints part of the window for an animation which doesn't
+    /// change any state.
+    pub(crate) fn repaint_animation(&mut self, rect: LocalRect, xform: LocalToWorld) {
+        self.add_dirty_rect(rect, xform);
+        self.animation_repaint = true;
     }
 
     pub(crate) fn set_dirty(&mut self) {
@@ -397,25 +917,43 @@ impl Context {
         }
     }
 
-    pub(crate) fn set_state<S: 'static>(&mut self, id: ViewId, value: S) {
-        self.state_map.insert(
-            id,
-            StateHolder {
-                state: Box::new(value),
-                dirty: false,
-            },
-        );
+    pub(crate) fn set_state<S: 'static>(&mut self, path: &IdPath, value: S) -> ViewId {
+        let id = path.view_id();
+        match self.state_map.get_mut(&id) {
+            Some(holder) => {
+                holder.state = Box::new(value);
+                holder.dirty = false;
+            }
+            None => {
+                self.state_map.insert(
+                    id,
+                    StateHolder {
+                        state: Box::new(value),
+                        dirty: false,
+                        path: path.clone(),
+                    },
+                );
+            }
+        }
+        id
     }
 
     pub(crate) fn is_dirty(&self, id: ViewId) -> bool {
         self.state_map[&id].dirty
     }
 
-    pub(crate) fn init_state<S: 'static, D: Fn() -> S + 'static>(&mut self, id: ViewId, func: &D) {
+    pub(crate) fn init_state<S: 'static, D: Fn() -> S + 'static>(
+        &mut self,
+        path: &IdPath,
+        func: &D,
+    ) -> ViewId {
+        let id = path.view_id();
         self.state_map.entry(id).or_insert_with(|| StateHolder {
             state: Box::new((func)()),
             dirty: false,
+            path: path.clone(),
         });
+        id
     }
 
     pub(crate) fn init_env<S: Clone + 'static, D: Fn() -> S + 'static>(&mut self, func: &D) -> S {
@@ -437,6 +975,284 @@ impl Context {
         old_value
     }
 
+    /// Asks for `anim` closures to run again on the next frame. Call this
+    /// each frame for as long as something is animating; the event loop
+    /// goes back to waiting for events once nobody asks.
+    pub fn request_animation_frame(&mut self) {
+        self.animation_requested = true;
+    }
+
+    /// Has anything asked for another animation frame?
+    pub fn animating(&self) -> bool {
+        self.animation_requested
+    }
+
+    /// Seconds since the previous animation frame, which is also what's
+    /// passed to `anim` closures. The first frame of an animation counts
+    /// as a sixtieth of a second.
+    pub fn frame_delta(&self) -> f32 {
+        self.frame_delta
+    }
+
+    /// Current time according to the clock.
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Calls a function once, after a delay.
+    pub fn after(
+        &mut self,
+        delay: Duration,
+        f: impl FnOnce(&mut Context) + 'static,
+    ) -> TimerHandle {
+        self.add_timer(delay, TimerFunc::Once(Box::new(f)), None)
+    }
+
+    /// Calls a function repeatedly, waiting `interval` before each call.
+    /// If updates are late, missed calls are skipped rather than made
+    /// all at once.
+    pub fn every(
+        &mut self,
+        interval: Duration,
+        f: impl FnMut(&mut Context) + 'static,
+    ) -> TimerHandle {
+        self.add_timer(interval, TimerFunc::Repeat(interval, Box::new(f)), None)
+    }
+
+    /// How long until the next timer is due, if there are any timers.
+    pub fn next_timer(&self) -> Option<Duration> {
+        let now = self.clock.now();
+        self.timers
+            .values()
+            .map(|timer| timer.deadline.saturating_sub(now))
+            .min()
+    }
+
+    fn add_timer(
+        &mut self,
+        delay: Duration,
+        func: TimerFunc,
+        owner: Option<ViewId>,
+    ) -> TimerHandle {
+        let id = self.next_timer_id;
+        self.next_timer_id += 1;
+        self.timers.insert(
+            id,
+            Timer {
+                deadline: self.clock.now() + delay,
+                func: Some(func),
+                owner,
+            },
+        );
+        TimerHandle { id }
+    }
+
+    /// Starts or updates the repeating timer belonging to a view.
+    pub(crate) fn view_timer(
+        &mut self,
+        owner: ViewId,
+        interval: Duration,
+        f: impl FnMut(&mut Context) + 'static,
+    ) {
+        let now = self.clock.now();
+        let existing = self
+            .timers
+            .values_mut()
+            .find(|timer| timer.owner == Some(owner));
+
+        match existing {
+            Some(timer) => {
+                // Restart if the interval changed.
+                if let Some(TimerFunc::Repeat(old_interval, _)) = &timer.func {
+                    if *old_interval != interval {
+                        timer.deadline = now + interval;
+                    }
+                }
+                timer.func = Some(TimerFunc::Repeat(interval, Box::new(f)));
+            }
+            None => {
+                self.add_timer(
+                    interval,
+                    TimerFunc::Repeat(interval, Box::new(f)),
+                    Some(owner),
+                );
+            }
+        }
+    }
+
+    /// Calls the functions of timers which are due, earliest first.
+    fn run_timers(&mut self) {
+        let now = self.clock.now();
+        let mut due: Vec<(Duration, u64)> = self
+            .timers
+            .iter()
+            .filter(|(_, timer)| timer.deadline <= now)
+            .map(|(id, timer)| (timer.deadline, *id))
+            .collect();
+        due.sort();
+
+        for (_, id) in due {
+            let func = match self.timers.get_mut(&id).and_then(|timer| timer.func.take()) {
+                Some(func) => func,
+                None => continue,
+            };
+            match func {
+                TimerFunc::Once(f) => {
+                    self.timers.remove(&id);
+                    f(self);
+                }
+                TimerFunc::Repeat(interval, mut f) => {
+                    f(self);
+
+                    // Unless it was cancelled, schedule the next call.
+                    if let Some(timer) = self.timers.get_mut(&id) {
+                        timer.deadline += interval;
+                        if timer.deadline <= now {
+                            timer.deadline = now + interval;
+                        }
+                        if timer.func.is_none() {
+                            timer.func = Some(TimerFunc::Repeat(interval, f));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Runs a future on the UI thread, then calls `then` with its result.
+    /// The future is polled during updates, whenever it's woken, so it
+    /// must not block. See `task` for moving blocking work off the UI thread.
+    ///
+    /// ```
+    /// # use rui::*;
+    /// state(
+    ///     || String::new(),
+    ///     |preset, cx| {
+    ///         button(text(&cx[preset]), move |cx| {
+    ///             cx.spawn(async { "loaded".to_string() }, move |cx, name| cx[preset] = name);
+    ///         })
+    ///     },
+    /// );
+    /// ```
+    pub fn spawn<T: 'static>(
+        &mut self,
+        future: impl Future<Output = T> + 'static,
+        then: impl FnOnce(&mut Context, T) + 'static,
+    ) -> TaskHandle {
+        let id = self.add_task(future, then, None, 0);
+        TaskHandle { id }
+    }
+
+    /// Sets how to wake the event loop when a spawned future can make
+    /// progress. It may be called from any thread, and should lead to
+    /// another `update`. The winit event loop sets this up itself.
+    pub fn set_wake(&mut self, wake: impl Fn() + Send + Sync + 'static) {
+        self.wake = Arc::new(wake);
+    }
+
+    /// Waker which only wakes the event loop.
+    pub(crate) fn event_loop_waker(&self) -> std::task::Waker {
+        futures::task::waker(Arc::new(EventLoopWaker {
+            wake: self.wake.clone(),
+        }))
+    }
+
+    fn add_task<T: 'static>(
+        &mut self,
+        future: impl Future<Output = T> + 'static,
+        then: impl FnOnce(&mut Context, T) + 'static,
+        owner: Option<ViewId>,
+        key: u64,
+    ) -> u64 {
+        let id = self.next_task_id;
+        self.next_task_id += 1;
+        let future = async move {
+            let value = future.await;
+            Box::new(move |cx: &mut Context| then(cx, value)) as Box<dyn FnOnce(&mut Context)>
+        };
+        self.tasks.insert(
+            id,
+            Task {
+                future: Some(Box::pin(future)),
+                owner,
+                key,
+            },
+        );
+
+        // Poll it for the first time.
+        self.ready_tasks.lock().unwrap().push(id);
+        (self.wake)();
+        id
+    }
+
+    /// Starts the task belonging to a view, unless it was already
+    /// started with the same key. A task with a different key is
+    /// cancelled.
+    pub(crate) fn view_task<T: 'static, Fut: Future<Output = T> + 'static>(
+        &mut self,
+        owner: ViewId,
+        key: u64,
+        future: impl FnOnce() -> Fut,
+        then: impl FnOnce(&mut Context, T) + 'static,
+    ) {
+        let existing = self
+            .tasks
+            .iter()
+            .find(|(_, task)| task.owner == Some(owner))
+            .map(|(id, task)| (*id, task.key));
+
+        match existing {
+            Some((_, old_key)) if old_key == key => (),
+            _ => {
+                if let Some((id, _)) = existing {
+                    self.tasks.remove(&id);
+                }
+                self.add_task(future(), then, Some(owner), key);
+            }
+        }
+    }
+
+    /// Polls the tasks which were woken.
+    fn poll_tasks(&mut self) {
+        let ready = std::mem::take(&mut *self.ready_tasks.lock().unwrap());
+
+        for id in ready {
+            let mut future = match self.tasks.get_mut(&id).and_then(|task| task.future.take()) {
+                Some(future) => future,
+                None => continue,
+            };
+
+            let waker = futures::task::waker(Arc::new(TaskWaker {
+                id,
+                ready: self.ready_tasks.clone(),
+                wake: self.wake.clone(),
+            }));
+            let mut context = std::task::Context::from_waker(&waker);
+
+            match future.as_mut().poll(&mut context) {
+                std::task::Poll::Ready(then) => {
+                    // Views remember their finished tasks, so they
+                    // aren't started again.
+                    if self.tasks.get(&id).is_some_and(|task| task.owner.is_none()) {
+                        self.tasks.remove(&id);
+                    }
+                    then(self);
+                }
+                std::task::Poll::Pending => {
+                    // Unless it was cancelled, keep it for next time.
+                    if let Some(task) = self.tasks.get_mut(&id) {
+                        task.future = Some(future);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces the clock used to time animations and timers.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
     pub fn get<S>(&self, id: StateHandle<S>) -> &S
     where
         S: 'static,
@@ -0,0 +1,553 @@
+use crate::*;
+use std::fmt;
+use std::path::Path;
+
+/// A paint captured by `Recorder`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordedPaint {
+    Color(Color),
+    Gradient {
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    },
+}
+
+/// A drawing command captured by `Recorder`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    Save,
+    Restore,
+    Translate(LocalOffset),
+    Scale(LocalOffset),
+    Scissor(LocalRect),
+    FillRect {
+        rect: LocalRect,
+        radius: f32,
+        paint: RecordedPaint,
+    },
+    FillCircle {
+        center: LocalPoint,
+        radius: f32,
+        paint: RecordedPaint,
+    },
+    StrokeRect {
+        min: LocalPoint,
+        max: LocalPoint,
+        radius: f32,
+        width: f32,
+        paint: RecordedPaint,
+    },
+    StrokeArc {
+        center: LocalPoint,
+        radius: f32,
+        width: f32,
+        rotation: f32,
+        aperture: f32,
+        paint: RecordedPaint,
+    },
+    StrokeSegment {
+        a: LocalPoint,
+        b: LocalPoint,
+        width: f32,
+        paint: RecordedPaint,
+    },
+    StrokeBezier {
+        a: LocalPoint,
+        b: LocalPoint,
+        c: LocalPoint,
+        width: f32,
+        paint: RecordedPaint,
+    },
+    Text {
+        text: String,
+        size: u32,
+        color: Color,
+        max_width: Option<f32>,
+    },
+}
+
+/// Everything drawn during a `View::draw` pass, in order.
+///
+/// The `Display` implementation writes one command per line and is
+/// stable, so it can be used for golden file tests.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DisplayList {
+    pub commands: Vec<DrawCommand>,
+}
+
+/// Formats numbers so small floating point differences don't
+/// change snapshots.
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = format!("{:.3}", self.0);
+        let s = s.trim_end_matches('0').trim_end_matches('.');
+        if s == "-0" {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", s)
+        }
+    }
+}
+
+struct Pt(LocalPoint);
+
+impl fmt::Display for Pt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", Num(self.0.x), Num(self.0.y))
+    }
+}
+
+impl fmt::Display for RecordedPaint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordedPaint::Color(c) => write!(
+                f,
+                "color({}, {}, {}, {})",
+                Num(c.r),
+                Num(c.g),
+                Num(c.b),
+                Num(c.a)
+            ),
+            RecordedPaint::Gradient {
+                start,
+                end,
+                inner_color,
+                outer_color,
+                glow,
+            } => write!(
+                f,
+                "gradient({} {} -> {} {} glow={})",
+                Pt(*start),
+                RecordedPaint::Color(*inner_color),
+                Pt(*end),
+                RecordedPaint::Color(*outer_color),
+                Num(*glow)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawCommand::Save => write!(f, "save"),
+            DrawCommand::Restore => write!(f, "restore"),
+            DrawCommand::Translate(v) => write!(f, "translate {}", Pt(v.to_point())),
+            DrawCommand::Scale(v) => write!(f, "scale {}", Pt(v.to_point())),
+            DrawCommand::Scissor(r) => {
+                write!(f, "scissor {} {}", Pt(r.min()), Pt(r.max()))
+            }
+            DrawCommand::FillRect {
+                rect,
+                radius,
+                paint,
+            } => write!(
+                f,
+                "fill_rect {} {} radius={} {}",
+                Pt(rect.min()),
+                Pt(rect.max()),
+                Num(*radius),
+                paint
+            ),
+            DrawCommand::FillCircle {
+                center,
+                radius,
+                paint,
+            } => write!(
+                f,
+                "fill_circle {} radius={} {}",
+                Pt(*center),
+                Num(*radius),
+                paint
+            ),
+            DrawCommand::StrokeRect {
+                min,
+                max,
+                radius,
+                width,
+                paint,
+            } => write!(
+                f,
+                "stroke_rect {} {} radius={} width={} {}",
+                Pt(*min),
+                Pt(*max),
+                Num(*radius),
+                Num(*width),
+                paint
+            ),
+            DrawCommand::StrokeArc {
+                center,
+                radius,
+                width,
+                rotation,
+                aperture,
+                paint,
+            } => write!(
+                f,
+                "stroke_arc {} radius={} width={} rotation={} aperture={} {}",
+                Pt(*center),
+                Num(*radius),
+                Num(*width),
+                Num(*rotation),
+                Num(*aperture),
+                paint
+            ),
+            DrawCommand::StrokeSegment { a, b, width, paint } => write!(
+                f,
+                "stroke_segment {} {} width={} {}",
+                Pt(*a),
+                Pt(*b),
+                Num(*width),
+                paint
+            ),
+            DrawCommand::StrokeBezier {
+                a,
+                b,
+                c,
+                width,
+                paint,
+            } => write!(
+                f,
+                "stroke_bezier {} {} {} width={} {}",
+                Pt(*a),
+                Pt(*b),
+                Pt(*c),
+                Num(*width),
+                paint
+            ),
+            DrawCommand::Text {
+                text,
+                size,
+                color,
+                max_width,
+            } => {
+                write!(
+                    f,
+                    "text {:?} size={} {}",
+                    text,
+                    size,
+                    RecordedPaint::Color(*color)
+                )?;
+                if let Some(w) = max_width {
+                    write!(f, " max_width={}", Num(*w))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for DisplayList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for command in &self.commands {
+            writeln!(f, "{}", command)?;
+        }
+        Ok(())
+    }
+}
+
+impl DisplayList {
+    /// Compares the display list against a golden file, panicking if they differ.
+    ///
+    /// Set the `RUI_UPDATE_SNAPSHOTS` environment variable to write the
+    /// golden file instead.
+    pub fn assert_snapshot(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual = self.to_string();
+
+        if std::env::var_os("RUI_UPDATE_SNAPSHOTS").is_some() {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).unwrap();
+            }
+            std::fs::write(path, &actual).unwrap();
+            return;
+        }
+
+        let expected = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(err) => panic!(
+                "couldn't read snapshot {:?} ({}), run with RUI_UPDATE_SNAPSHOTS=1 to create it",
+                path, err
+            ),
+        };
+
+        if expected != actual {
+            panic!(
+                "snapshot {:?} doesn't match.\n--- expected:\n{}--- actual:\n{}",
+                path, expected, actual
+            );
+        }
+    }
+}
+
+/// A `Renderer` which records drawing into a `DisplayList` instead of
+/// drawing to the screen. Text is measured with `MonospaceText`
+/// unless another `TextMeasurer` is provided.
+pub struct Recorder {
+    list: DisplayList,
+    paints: Vec<RecordedPaint>,
+    xform: LocalToWorld,
+    stack: Vec<LocalToWorld>,
+    pub(crate) text: Box<dyn TextMeasurer>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self {
+            list: DisplayList::default(),
+            paints: vec![],
+            xform: LocalToWorld::identity(),
+            stack: vec![],
+            text: Box::new(MonospaceText::default()),
+        }
+    }
+
+    /// Replaces the text measurement.
+    pub fn text_measurer(self, text: impl TextMeasurer + 'static) -> Self {
+        Self {
+            text: Box::new(text),
+            ..self
+        }
+    }
+
+    /// Returns what was drawn.
+    pub fn finish(self) -> DisplayList {
+        self.list
+    }
+
+    fn push(&mut self, command: DrawCommand) {
+        self.list.commands.push(command)
+    }
+
+    fn paint(&self, paint: PaintIndex) -> RecordedPaint {
+        self.paints[paint.index].clone()
+    }
+
+    fn add_paint(&mut self, paint: RecordedPaint) -> PaintIndex {
+        self.paints.push(paint);
+        PaintIndex {
+            index: self.paints.len() - 1,
+        }
+    }
+}
+
+impl TextMeasurer for Recorder {
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        self.text.text_bounds(text, size, max_width)
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        self.text.glyph_positions(text, size, max_width)
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        self.text.line_metrics(text, size, max_width)
+    }
+}
+
+impl Renderer for Recorder {
+    fn save(&mut self) {
+        self.stack.push(self.xform);
+        self.push(DrawCommand::Save)
+    }
+
+    fn restore(&mut self) {
+        if let Some(xform) = self.stack.pop() {
+            self.xform = xform;
+        }
+        self.push(DrawCommand::Restore)
+    }
+
+    fn translate(&mut self, offset: LocalOffset) {
+        self.xform = self.xform.pre_translate(offset);
+        self.push(DrawCommand::Translate(offset))
+    }
+
+    fn scale(&mut self, scale: LocalOffset) {
+        self.xform = self.xform.pre_scale(scale.x, scale.y);
+        self.push(DrawCommand::Scale(scale))
+    }
+
+    fn current_transform(&self) -> LocalToWorld {
+        self.xform
+    }
+
+    fn scissor(&mut self, rect: LocalRect) {
+        self.push(DrawCommand::Scissor(rect))
+    }
+
+    fn color_paint(&mut self, color: Color) -> PaintIndex {
+        self.add_paint(RecordedPaint::Color(color))
+    }
+
+    fn linear_gradient(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    ) -> PaintIndex {
+        self.add_paint(RecordedPaint::Gradient {
+            start,
+            end,
+            inner_color,
+            outer_color,
+            glow,
+        })
+    }
+
+    fn fill_rect(&mut self, rect: LocalRect, radius: f32, paint: PaintIndex) {
+        let paint = self.paint(paint);
+        self.push(DrawCommand::FillRect {
+            rect,
+            radius,
+            paint,
+        })
+    }
+
+    fn fill_circle(&mut self, center: LocalPoint, radius: f32, paint: PaintIndex) {
+        let paint = self.paint(paint);
+        self.push(DrawCommand::FillCircle {
+            center,
+            radius,
+            paint,
+        })
+    }
+
+    fn stroke_rect(
+        &mut self,
+        min: LocalPoint,
+        max: LocalPoint,
+        radius: f32,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        let paint = self.paint(paint);
+        self.push(DrawCommand::StrokeRect {
+            min,
+            max,
+            radius,
+            width,
+            paint,
+        })
+    }
+
+    fn stroke_arc(
+        &mut self,
+        center: LocalPoint,
+        radius: f32,
+        width: f32,
+        rotation: f32,
+        aperture: f32,
+        paint: PaintIndex,
+    ) {
+        let paint = self.paint(paint);
+        self.push(DrawCommand::StrokeArc {
+            center,
+            radius,
+            width,
+            rotation,
+            aperture,
+            paint,
+        })
+    }
+
+    fn stroke_segment(&mut self, a: LocalPoint, b: LocalPoint, width: f32, paint: PaintIndex) {
+        let paint = self.paint(paint);
+        self.push(DrawCommand::StrokeSegment { a, b, width, paint })
+    }
+
+    fn stroke_bezier(
+        &mut self,
+        a: LocalPoint,
+        b: LocalPoint,
+        c: LocalPoint,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        let paint = self.paint(paint);
+        self.push(DrawCommand::StrokeBezier {
+            a,
+            b,
+            c,
+            width,
+            paint,
+        })
+    }
+
+    fn text(&mut self, text: &str, size: u32, color: Color, max_width: Option<f32>) {
+        self.push(DrawCommand::Text {
+            text: text.into(),
+            size,
+            color,
+            max_width,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn snapshot_path(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(format!("{}.txt", name))
+    }
+
+    #[test]
+    fn test_record_button() {
+        let mut h = TestHarness::new(button("click me", |_| ()), [200.0, 100.0]);
+        h.draw().assert_snapshot(snapshot_path("button"));
+    }
+
+    #[test]
+    fn test_record_hslider() {
+        let ui = state(|| 0.5, |s, _| hslider(s));
+        let mut h = TestHarness::new(ui, [200.0, 40.0]);
+        // The slider learns its width when first drawn.
+        h.draw();
+        h.update();
+        h.draw().assert_snapshot(snapshot_path("hslider"));
+    }
+
+    #[test]
+    fn test_record_knob() {
+        let ui = state(|| 0.25, |s, _| knob(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        h.draw().assert_snapshot(snapshot_path("knob"));
+    }
+
+    #[test]
+    fn test_record_transform() {
+        let mut r = Recorder::new();
+        r.save();
+        r.translate([10.0, 20.0].into());
+        assert_eq!(
+            r.current_transform().transform_point([1.0, 1.0].into()),
+            [11.0, 21.0].into()
+        );
+        r.restore();
+        assert_eq!(r.current_transform(), LocalToWorld::identity());
+
+        let paint = r.color_paint(BLACK);
+        r.fill_circle([0.0, 0.0].into(), 2.0, paint);
+        assert_eq!(
+            r.finish().to_string(),
+            "save\ntranslate (10, 20)\nrestore\nfill_circle (0, 0) radius=2 color(0, 0, 0, 1)\n"
+        );
+    }
+}
@@ -25,12 +25,52 @@ pub enum Event {
         position: LocalPoint,
     },
 
+    /// Mouse moved, in window coordinates.
+    PointerMove { position: LocalPoint },
+
+    /// Mouse entered the window.
+    PointerEnter,
+
+    /// Mouse left the window.
+    PointerLeave,
+
+    /// Scroll wheel or trackpad scroll.
+    Scroll {
+        /// Where the pointer is, in window coordinates.
+        position: LocalPoint,
+
+        /// How far the content should move, in window coordinates.
+        delta: LocalOffset,
+
+        phase: GestureState,
+
+        /// True for trackpads and other devices which scroll
+        /// by pixels rather than by lines.
+        precise: bool,
+    },
+
     /// Menu command.
     Command(String),
 
-    /// Key press.
+    /// Key press. `Key::Character` is the key as typed on a US layout,
+    /// for shortcuts. Text entry should use `TextInput` instead.
     Key(Key),
 
+    /// Text typed or committed by an input method, such as `"é"` or
+    /// `"日本"`.
+    TextInput(String),
+
+    /// Text an input method is composing, shown at the caret until
+    /// it's committed with `TextInput`. An empty `preedit` means the
+    /// composition was cancelled or cleared.
+    ImeComposition {
+        preedit: String,
+
+        /// Byte range of the selection within `preedit`, or `None` to
+        /// hide the caret.
+        cursor: Option<(usize, usize)>,
+    },
+
     /// Animation.
     Anim,
 }
@@ -42,12 +82,60 @@ impl Event {
             Event::TouchBegin { position, .. } => *position += offset,
             Event::TouchMove { position, .. } => *position += offset,
             Event::TouchEnd { position, .. } => *position += offset,
+            Event::PointerMove { position } => *position += offset,
+            Event::Scroll { position, .. } => *position += offset,
             _ => (),
         }
         event
     }
 }
 
+/// Maps platform touch IDs to the slots used as `id` in touch events,
+/// and tracks where each touch is so moves have the right delta.
+///
+/// Slot 0 is left for the mouse. Use one when forwarding platform
+/// touches to `RuiHost::handle_event`.
+#[derive(Clone, Debug, Default)]
+pub struct TouchTracker {
+    slots: [Option<(u64, LocalPoint)>; 16],
+}
+
+impl TouchTracker {
+    /// Assigns a free slot to a new touch. Returns `None` if all
+    /// slots are in use.
+    pub fn begin(&mut self, touch: u64, position: LocalPoint) -> Option<Event> {
+        let id = (1..self.slots.len()).find(|i| self.slots[*i].is_none())?;
+        self.slots[id] = Some((touch, position));
+        Some(Event::TouchBegin { id, position })
+    }
+
+    /// Moves a touch. Returns `None` if the touch hasn't begun.
+    pub fn moved(&mut self, touch: u64, position: LocalPoint) -> Option<Event> {
+        let id = self.slot(touch)?;
+        let previous = self.slots[id].map(|(_, p)| p).unwrap_or(position);
+        self.slots[id] = Some((touch, position));
+        Some(Event::TouchMove {
+            id,
+            position,
+            delta: position - previous,
+        })
+    }
+
+    /// Ends a touch and frees its slot. Returns `None` if the touch
+    /// hasn't begun.
+    pub fn end(&mut self, touch: u64, position: LocalPoint) -> Option<Event> {
+        let id = self.slot(touch)?;
+        self.slots[id] = None;
+        Some(Event::TouchEnd { id, position })
+    }
+
+    fn slot(&self, touch: u64) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| matches!(slot, Some((t, _)) if *t == touch))
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum MouseButton {
     Left,
@@ -55,7 +143,7 @@ pub enum MouseButton {
     Center,
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct KeyboardModifiers {
     pub shift: bool,
     pub control: bool,
@@ -63,7 +151,27 @@ pub struct KeyboardModifiers {
     pub command: bool,
 }
 
-#[derive(Copy, Clone, Debug)]
+impl KeyboardModifiers {
+    /// Just the platform's primary modifier for shortcuts: command
+    /// on macOS and control elsewhere.
+    pub fn primary() -> Self {
+        let mac = cfg!(target_os = "macos");
+        Self {
+            command: mac,
+            control: !mac,
+            ..Default::default()
+        }
+    }
+
+    /// Are characters typed with these modifiers shortcuts rather than
+    /// text? Windows reports AltGr as Ctrl+Alt, and AltGr types
+    /// characters such as `@` and `€` on many layouts.
+    pub(crate) fn shortcut(&self) -> bool {
+        (self.control || self.command) && !self.alt
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Key {
     Character(char),
 
@@ -95,6 +203,7 @@ pub enum Key {
     F12,
 }
 
+/// A letter key. Converts to a `Shortcut` using the primary modifier.
 #[derive(Clone, Debug, Eq, PartialEq, Copy)]
 pub enum HotKey {
     KeyA,
@@ -124,3 +233,11 @@ pub enum HotKey {
     KeyY,
     KeyZ,
 }
+
+impl HotKey {
+    /// The character typed by the key.
+    pub fn character(self) -> char {
+        // Variants are in alphabetical order.
+        (b'a' + self as u8) as char
+    }
+}
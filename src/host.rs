@@ -0,0 +1,188 @@
+use crate::*;
+use std::sync::Arc;
+
+/// Runs a view tree inside an event loop owned by someone else, such as
+/// a plugin host, a game engine or an existing wgpu application.
+///
+/// The host forwards events with `handle_event`, calls `update` once per
+/// batch of events and calls `render` when `update` returns true.
+/// Positions in events are in points, with the origin at the bottom left
+/// of the window.
+///
+/// Futures started with `Context::spawn` call the function passed to
+/// `Context::set_wake` when they can make progress, from any thread.
+/// Call `update` soon after, to poll them.
+pub struct RuiHost<V> {
+    /// The UI state. Use it to read and write state through `StateHandle`s.
+    pub cx: Context,
+
+    /// Root of the view tree.
+    view: V,
+
+    vger: Vger,
+
+    /// Window size in points.
+    window_size: LocalSize,
+
+    /// Pixels per point.
+    scale: f32,
+
+    /// AccessKit nodes from the last update.
+    access_nodes: Vec<(accesskit::NodeId, accesskit::Node)>,
+}
+
+impl<V: View> RuiHost<V> {
+    /// Creates a host which renders with the given device into
+    /// textures of the given format.
+    pub fn new(
+        view: V,
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            cx: Context::new(),
+            view,
+            vger: Vger::new(device, queue, format),
+            window_size: LocalSize::zero(),
+            scale: 1.0,
+            access_nodes: vec![],
+        }
+    }
+
+    /// Sends an event to the view tree.
+    pub fn handle_event(&mut self, event: Event) {
+        self.cx.process(&self.view, &event);
+    }
+
+    /// Sets the pressed mouse button. Call this before sending the
+    /// `TouchBegin` or `TouchEnd` for a mouse click.
+    pub fn set_mouse_button(&mut self, button: Option<MouseButton>) {
+        self.cx.mouse_button = button;
+    }
+
+    /// Whether a view wants the cursor hidden and locked in place,
+    /// such as while dragging a knob.
+    pub fn grab_cursor(&self) -> bool {
+        self.cx.grab_cursor
+    }
+
+    /// Whether a view asked for another animation frame. While this is
+    /// true, call `update` again after about a frame, even without events.
+    pub fn animating(&self) -> bool {
+        self.cx.animating()
+    }
+
+    /// How long until a timer is due. Call `update` by then, even
+    /// without events.
+    pub fn next_timer(&self) -> Option<std::time::Duration> {
+        self.cx.next_timer()
+    }
+
+    /// Runs timers and animations, cleans up state and updates the view tree for a
+    /// window of `size` points with `scale` pixels per point.
+    ///
+    /// Returns true if the UI needs to be redrawn.
+    pub fn update(&mut self, size: impl Into<LocalSize>, scale: f32) -> bool {
+        let size = size.into();
+        let resized = size != self.window_size || scale != self.scale;
+        if scale != self.scale {
+            // Text may measure differently at the new scale.
+            self.cx.invalidate_layout();
+        }
+        self.window_size = size;
+        self.scale = scale;
+        self.cx.update(
+            &self.view,
+            &mut self.vger,
+            &mut self.access_nodes,
+            size.cast_unit(),
+        ) || resized
+    }
+
+    /// Draws the UI at the size passed to the last `update`.
+    ///
+    /// If the surface is configured with `TextureUsages::COPY_DST`, only
+    /// the parts of the UI which changed are repainted.
+    pub fn render(&mut self, render_info: RenderInfo) {
+        self.cx.render(
+            render_info,
+            &self.view,
+            &mut self.vger,
+            self.window_size.cast_unit(),
+            self.scale,
+        );
+    }
+
+    /// Draws the UI into a texture without presenting it, so rui can be
+    /// composited on top of another scene. Use `wgpu::LoadOp::Load` to
+    /// keep what's already in the texture. Everything is drawn each time.
+    pub fn render_to_texture(
+        &mut self,
+        texture_view: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        self.cx.render_to_texture(
+            &self.view,
+            &mut self.vger,
+            texture_view,
+            load,
+            self.window_size.cast_unit(),
+            self.scale,
+        );
+    }
+
+    /// Draws only what changed into a texture which still holds the
+    /// last frame, because nothing but rui draws into it. Composite the
+    /// texture yourself. `load` is used when everything is drawn.
+    pub fn render_changes_to_texture(
+        &mut self,
+        texture_view: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        self.cx.render_changes_to_texture(
+            &self.view,
+            &mut self.vger,
+            texture_view,
+            load,
+            self.window_size.cast_unit(),
+            self.scale,
+        );
+    }
+
+    /// Returns the menu commands declared by the view tree.
+    pub fn commands(&mut self) -> Vec<CommandInfo> {
+        let mut cmds = vec![];
+        self.cx.commands(&self.view, &mut cmds);
+        cmds
+    }
+
+    /// Returns the menu commands if they changed since the last call,
+    /// so native menus can be rebuilt. Send `Event::Command` with a
+    /// command's path when it's chosen. Hotkeys are handled already.
+    pub fn updated_commands(&mut self) -> Option<Vec<CommandInfo>> {
+        if std::mem::take(&mut self.cx.menu_commands_changed) {
+            Some(self.cx.menu_commands().to_vec())
+        } else {
+            None
+        }
+    }
+
+    /// AccessKit nodes generated by the last update.
+    pub fn access_nodes(&self) -> &[(accesskit::NodeId, accesskit::Node)] {
+        &self.access_nodes
+    }
+
+    /// Where text is being entered, in points from the bottom-left of
+    /// the window, or `None` if no text view has focus. Enable the
+    /// platform's input method while there is one, and put its
+    /// candidate window below this.
+    pub fn ime_rect(&self) -> Option<WorldRect> {
+        self.cx.ime_rect
+    }
+
+    /// The title the UI would like for its window.
+    pub fn window_title(&self) -> &str {
+        &self.cx.window_title
+    }
+}
@@ -0,0 +1,387 @@
+use crate::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// User changes to the shortcuts of menu commands, keyed by command
+/// path such as `"File:New"`. Commands without an entry keep the
+/// shortcut they were declared with.
+///
+/// Keymaps are stored in a restricted form of TOML: one `path = "shortcut"`
+/// pair per line, with comments. Keys and values are basic or literal
+/// strings (keys may also be bare), and each path may appear only once.
+/// Tables, arrays and multi-line strings aren't supported. An empty
+/// shortcut removes the command's shortcut:
+///
+/// ```
+/// # use rui::*;
+/// let keymap = Keymap::parse(r#"
+///     "File:New" = "Primary+Shift+N"
+///     "Edit:Redo" = ""
+/// "#).unwrap();
+/// assert_eq!(keymap.get("Edit:Redo"), Some(None));
+/// ```
+///
+/// Install one with `Context::set_keymap`, which reports shortcuts that
+/// end up shared by more than one command.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Keymap {
+    overrides: HashMap<String, Option<Shortcut>>,
+}
+
+/// Why a keymap couldn't be loaded.
+#[derive(Debug)]
+pub enum KeymapError {
+    Io(std::io::Error),
+
+    /// The file isn't in the keymap format, repeats a path, or has an
+    /// invalid shortcut.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Io(err) => write!(f, "{}", err),
+            KeymapError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+impl From<std::io::Error> for KeymapError {
+    fn from(err: std::io::Error) -> Self {
+        KeymapError::Io(err)
+    }
+}
+
+/// Commands which share a shortcut.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeymapConflict {
+    pub shortcut: Shortcut,
+
+    /// Paths of the commands, in the order they were declared.
+    pub paths: Vec<String>,
+}
+
+impl Keymap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a keymap from the TOML subset described above.
+    pub fn parse(src: &str) -> Result<Self, KeymapError> {
+        let mut keymap = Self::new();
+        for (i, line) in src.lines().enumerate() {
+            let error = |message: &str| KeymapError::Parse {
+                line: i + 1,
+                message: message.into(),
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                return Err(error("tables aren't supported in keymaps"));
+            }
+
+            let (path, rest) = parse_key(line).ok_or_else(|| error("expected a command path"))?;
+            let rest = rest
+                .trim_start()
+                .strip_prefix('=')
+                .ok_or_else(|| error("expected ="))?;
+            let (value, rest) =
+                parse_string(rest.trim_start()).ok_or_else(|| error("expected a string"))?;
+            let rest = rest.trim();
+            if !(rest.is_empty() || rest.starts_with('#')) {
+                return Err(error("unexpected text after value"));
+            }
+
+            let shortcut = if value.is_empty() {
+                None
+            } else {
+                Some(
+                    value
+                        .parse()
+                        .map_err(|err: ParseShortcutError| error(&err.to_string()))?,
+                )
+            };
+            if keymap.overrides.insert(path, shortcut).is_some() {
+                return Err(error("duplicate command path"));
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Reads a keymap from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeymapError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Writes the keymap as TOML, in a form `parse` reads.
+    pub fn to_toml(&self) -> String {
+        let mut paths: Vec<_> = self.overrides.keys().collect();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| {
+                let shortcut = self.overrides[*path]
+                    .map(|s| s.to_string())
+                    .unwrap_or_default();
+                format!("{} = {}\n", quote(path), quote(&shortcut))
+            })
+            .collect()
+    }
+
+    /// Writes the keymap to a TOML file.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_toml())
+    }
+
+    /// Changes the shortcut of a command. `None` removes it.
+    pub fn bind(&mut self, path: &str, shortcut: Option<Shortcut>) {
+        self.overrides.insert(path.into(), shortcut);
+    }
+
+    /// Goes back to the shortcut the command was declared with.
+    pub fn reset(&mut self, path: &str) {
+        self.overrides.remove(path);
+    }
+
+    /// The user's shortcut for a command, or `None` if it hasn't been
+    /// changed.
+    pub fn get(&self, path: &str) -> Option<Option<Shortcut>> {
+        self.overrides.get(path).copied()
+    }
+
+    /// Commands with the user's shortcuts in place of the declared ones.
+    pub fn apply(&self, commands: &[CommandInfo]) -> Vec<CommandInfo> {
+        commands
+            .iter()
+            .map(|cmd| CommandInfo {
+                key: self.get(&cmd.path).unwrap_or(cmd.key),
+                ..cmd.clone()
+            })
+            .collect()
+    }
+
+    /// Shortcuts used by more than one command, once the user's
+    /// shortcuts are applied.
+    pub fn conflicts(&self, commands: &[CommandInfo]) -> Vec<KeymapConflict> {
+        let mut conflicts: Vec<KeymapConflict> = vec![];
+        for cmd in self.apply(commands) {
+            let shortcut = match cmd.key {
+                Some(shortcut) => shortcut,
+                None => continue,
+            };
+            match conflicts.iter_mut().find(|c| c.shortcut == shortcut) {
+                Some(conflict) => {
+                    if !conflict.paths.contains(&cmd.path) {
+                        conflict.paths.push(cmd.path)
+                    }
+                }
+                None => conflicts.push(KeymapConflict {
+                    shortcut,
+                    paths: vec![cmd.path],
+                }),
+            }
+        }
+        conflicts.retain(|c| c.paths.len() > 1);
+        conflicts
+    }
+}
+
+/// Parses a quoted or bare TOML key.
+fn parse_key(s: &str) -> Option<(String, &str)> {
+    if s.starts_with('"') || s.starts_with('\'') {
+        return parse_string(s);
+    }
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(s.len());
+    if end == 0 {
+        None
+    } else {
+        Some((s[..end].into(), &s[end..]))
+    }
+}
+
+/// Parses a TOML basic or literal string, returning it and the rest of
+/// the line.
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    if let Some(rest) = s.strip_prefix('\'') {
+        let end = rest.find('\'')?;
+        return Some((rest[..end].into(), &rest[end + 1..]));
+    }
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &s[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                '"' => value.push('"'),
+                '\\' => value.push('\\'),
+                'b' => value.push('\u{8}'),
+                't' => value.push('\t'),
+                'n' => value.push('\n'),
+                'f' => value.push('\u{c}'),
+                'r' => value.push('\r'),
+                escape @ ('u' | 'U') => {
+                    let len = if escape == 'u' { 4 } else { 8 };
+                    let hex: String = (0..len)
+                        .map(|_| chars.next().map(|(_, c)| c).filter(char::is_ascii_hexdigit))
+                        .collect::<Option<_>>()?;
+                    value.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                _ => return None,
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+fn quote(s: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn cmd(path: &str, key: &str) -> CommandInfo {
+        CommandInfo {
+            path: path.into(),
+            key: key.parse().ok(),
+            enabled: true,
+            checked: None,
+        }
+    }
+
+    #[test]
+    fn test_keymap_parse() {
+        let keymap = Keymap::parse(
+            r#"
+            # Comment
+            "File:New" = "Ctrl+Shift+N"   # trailing comment
+            "Edit:Say \"Hi\"" = ""
+            Refresh = "F5"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(keymap.get("File:New"), Some("Ctrl+Shift+N".parse().ok()));
+        assert_eq!(keymap.get("Edit:Say \"Hi\""), Some(None));
+        assert_eq!(keymap.get("Refresh"), Some("F5".parse().ok()));
+        assert_eq!(keymap.get("File:Open"), None);
+        assert_eq!(Keymap::parse(&keymap.to_toml()).unwrap(), keymap);
+
+        let keymap = Keymap::parse(
+            r#"
+            'C:\Tab' = 'Ctrl+\'
+            "Tab\tStop\u00e9" = "F1"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(keymap.get("C:\\Tab"), Some("Ctrl+\\".parse().ok()));
+        assert_eq!(keymap.get("Tab\tStop\u{e9}"), Some("F1".parse().ok()));
+        assert_eq!(Keymap::parse(&keymap.to_toml()).unwrap(), keymap);
+
+        let line = |src: &str| match Keymap::parse(src) {
+            Err(KeymapError::Parse { line, .. }) => line,
+            _ => 0,
+        };
+        assert_eq!(line("\"File:New\" = \"Hyper+N\""), 1);
+        assert_eq!(line("\n\"File:New\" \"Ctrl+N\""), 2);
+        assert_eq!(line("\"File:New\" = \"Ctrl+N"), 1);
+        assert_eq!(line("\"File:New\" = \"Ctrl+N\" x"), 1);
+        assert_eq!(
+            line("\"File:New\" = \"Ctrl+N\"\nRefresh = \"F5\"\n'File:New' = \"\""),
+            3
+        );
+        assert_eq!(line("[keys]\n\"File:New\" = \"Ctrl+N\""), 1);
+        assert_eq!(line("\"File:New\\u00\" = \"Ctrl+N\""), 1);
+    }
+
+    #[test]
+    fn test_keymap_conflicts() {
+        let commands = [cmd("File:New", "Ctrl+N"), cmd("File:Open", "Ctrl+O")];
+        let mut keymap = Keymap::new();
+        assert!(keymap.conflicts(&commands).is_empty());
+
+        keymap.bind("File:Open", "Ctrl+N".parse().ok());
+        assert_eq!(keymap.apply(&commands)[1], cmd("File:Open", "Ctrl+N"));
+        assert_eq!(
+            keymap.conflicts(&commands),
+            vec![KeymapConflict {
+                shortcut: "Ctrl+N".parse().unwrap(),
+                paths: vec!["File:New".into(), "File:Open".into()],
+            }]
+        );
+
+        keymap.bind("File:New", None);
+        assert!(keymap.conflicts(&commands).is_empty());
+        keymap.reset("File:New");
+        assert_eq!(keymap.conflicts(&commands).len(), 1);
+    }
+
+    #[test]
+    fn test_keymap_hotkeys() {
+        let ui = state(
+            || 0,
+            |count, _| {
+                rectangle().command("File:New", "Ctrl+N".parse().ok(), move |cx| cx[count] += 1)
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let count = h.root_state::<i32>();
+        let conflicts =
+            h.cx.set_keymap(Keymap::parse("\"File:New\" = \"F2\"").unwrap());
+        assert!(conflicts.is_empty());
+        assert_eq!(h.cx.menu_commands(), &[cmd("File:New", "F2")]);
+
+        h.send_key(Key::F2);
+        assert_eq!(h.cx[count], 1);
+        h.cx.key_mods.control = true;
+        h.type_text("n");
+        assert_eq!(h.cx[count], 1);
+    }
+
+    #[test]
+    fn test_keymap_load_conflicts() {
+        let ui = hstack((
+            rectangle().command("File:New", "Ctrl+N".parse().ok(), |_| ()),
+            rectangle().command("File:Open", "Ctrl+O".parse().ok(), |_| ()),
+        ));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let conflicts =
+            h.cx.set_keymap(Keymap::parse("\"File:Open\" = \"Ctrl+N\"").unwrap());
+        assert_eq!(
+            conflicts,
+            vec![KeymapConflict {
+                shortcut: "Ctrl+N".parse().unwrap(),
+                paths: vec!["File:New".into(), "File:Open".into()],
+            }]
+        );
+        assert_eq!(h.cx.keymap_conflicts(), &conflicts[..]);
+
+        h.cx.set_keymap(Keymap::new());
+        assert!(h.cx.keymap_conflicts().is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 // #![feature(type_alias_impl_trait)]
 
 use vger::color::*;
-pub use vger::{LineMetrics, PaintIndex, Vger};
+pub use vger::{LineMetrics, Vger};
 
 #[cfg(feature = "winit")]
 #[macro_use]
@@ -19,6 +19,12 @@ pub use viewtuple::*;
 mod event;
 pub use event::*;
 
+mod shortcut;
+pub use shortcut::*;
+
+mod keymap;
+pub use keymap::*;
+
 mod lens;
 pub use lens::*;
 
@@ -28,6 +34,21 @@ pub use binding::*;
 mod context;
 pub use context::*;
 
+mod clock;
+pub use clock::*;
+
+mod timer;
+pub use timer::*;
+
+mod task;
+pub use task::*;
+
+mod animation;
+pub use animation::*;
+
+mod transition;
+pub use transition::*;
+
 mod views;
 pub use views::*;
 
@@ -46,6 +67,26 @@ pub use align::*;
 mod region;
 pub use region::*;
 
+mod test_harness;
+pub use test_harness::*;
+
+mod text_measurer;
+pub use text_measurer::*;
+
+mod renderer;
+pub use renderer::*;
+
+mod display_list;
+pub use display_list::*;
+
+mod host;
+pub use host::*;
+
+#[cfg(feature = "software")]
+mod software;
+#[cfg(feature = "software")]
+pub use software::*;
+
 #[cfg(feature = "winit")]
 mod winit_event_loop;
 
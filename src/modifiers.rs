@@ -1,10 +1,15 @@
 use crate::*;
 use accesskit::Role;
+use std::future::Future;
+use std::hash::Hash;
 use std::marker::PhantomData;
+use std::time::Duration;
 
 /// Modifiers common to all views.
 pub trait Modifiers: View + Sized {
     /// Calls a closure after rendering with context and delta time.
+    /// The closure runs after events, and again on the next frame if it
+    /// calls `cx.request_animation_frame()`.
     fn anim<F: Fn(&mut Context, f32) + 'static + Clone>(self, func: F) -> AnimView<Self, F> {
         AnimView::new(self, func)
     }
@@ -14,11 +19,16 @@ pub trait Modifiers: View + Sized {
         Background::new(self, background)
     }
 
-    /// Adds a menu command.
+    /// Adds a menu command, which runs when `key` is pressed.
+    ///
+    /// ```
+    /// # use rui::*;
+    /// rectangle().command("Edit:Redo", "Primary+Shift+Z".parse().ok(), |_| println!("redo"));
+    /// ```
     fn command<F: Fn(&mut Context) + 'static>(
         self,
         name: &str,
-        key: Option<HotKey>,
+        key: Option<Shortcut>,
         f: F,
     ) -> Command<Self, F> {
         Command::new(self, name.into(), key, f)
@@ -107,6 +117,14 @@ pub trait Modifiers: View + Sized {
         KeyView::new(self, f)
     }
 
+    /// Responds to typed text, including text committed by an input
+    /// method. Unlike `key`, this sees what the user meant to type on
+    /// any keyboard layout. Events are passed on to the view, so put
+    /// this outside any `key` modifier.
+    fn text_input<F: Fn(&mut Context, &str) + 'static>(self, f: F) -> TextInputView<Self, F> {
+        TextInputView::new(self, f)
+    }
+
     /// Applies an offset to the view in local space.
     fn offset<Off: Into<LocalOffset>>(self, offset: Off) -> Offset<Self> {
         Offset::new(self, offset.into())
@@ -146,6 +164,87 @@ pub trait Modifiers: View + Sized {
         Tap::new(self, TapFunc { f })
     }
 
+    /// Runs the future returned by `func` while the view exists, and
+    /// writes its result through `binding`. When `key` changes, the
+    /// future is dropped and a new one is started.
+    ///
+    /// The future is polled on the UI thread, so it must not block.
+    /// Do blocking work, such as reading files, on another thread
+    /// and await its result:
+    ///
+    /// ```
+    /// # use rui::*;
+    /// use futures::channel::oneshot;
+    ///
+    /// state(
+    ///     || None,
+    ///     |contents, cx| {
+    ///         text(cx[contents].as_deref().unwrap_or("loading..."))
+    ///             .task("preset.json", contents, |path| {
+    ///                 let (tx, rx) = oneshot::channel();
+    ///                 let path = path.to_string();
+    ///                 std::thread::spawn(move || tx.send(std::fs::read_to_string(path).ok()));
+    ///                 async move { rx.await.ok().flatten() }
+    ///             })
+    ///     },
+    /// );
+    /// ```
+    fn task<K: Hash + 'static, T: 'static, B: Binding<T>, Fut, F>(
+        self,
+        key: K,
+        binding: B,
+        func: F,
+    ) -> TaskView<Self, K, B, F>
+    where
+        Fut: Future<Output = T> + 'static,
+        F: Fn(&K) -> Fut + 'static,
+    {
+        TaskView::new(self, key, binding, func)
+    }
+
+    /// Calls `func` on the main thread with each message from `source`,
+    /// once per frame. Use `coalesce` on the result to only get the
+    /// latest message.
+    ///
+    /// Use a channel which can wake the event loop, such as
+    /// `futures::channel::mpsc`. A `std::sync::mpsc::Receiver` works
+    /// too, but has to be polled, which keeps an idle app waking up
+    /// ten times a second. See `MessageSource`.
+    ///
+    /// ```
+    /// # use rui::*;
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// let (send, recv) = futures::channel::mpsc::unbounded();
+    /// std::thread::spawn(move || send.unbounded_send(0.5).unwrap());
+    /// let recv = Rc::new(RefCell::new(recv));
+    /// state(
+    ///     || 0.0,
+    ///     move |level, cx| {
+    ///         rectangle()
+    ///             .size([20.0, 100.0 * cx[level]])
+    ///             .on_receive(recv.clone(), move |cx, value| cx[level] = value)
+    ///             .coalesce()
+    ///     },
+    /// );
+    /// ```
+    fn on_receive<R: MessageSource, F: Fn(&mut Context, R::Message) + 'static>(
+        self,
+        source: R,
+        func: F,
+    ) -> OnReceive<Self, R, F> {
+        OnReceive::new(self, source, func)
+    }
+
+    /// Calls a closure every `interval` for as long as the view exists.
+    fn timer<F: Fn(&mut Context) + 'static + Clone>(
+        self,
+        interval: Duration,
+        func: F,
+    ) -> TimerView<Self, F> {
+        TimerView::new(self, interval, func)
+    }
+
     /// Specify the title of the window.
     fn window_title(self, title: &str) -> TitleView<Self> {
         TitleView::new(self, title)
@@ -1,7 +1,7 @@
 use crate::*;
 
 /// Specifies how a region should be filled.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Paint {
     /// Fill a region with a solid color.
     Color(Color),
@@ -16,15 +16,15 @@ pub enum Paint {
 }
 
 impl Paint {
-    pub fn vger_paint(&self, vger: &mut Vger) -> PaintIndex {
+    pub fn renderer_paint(&self, renderer: &mut dyn Renderer) -> PaintIndex {
         match self {
-            Paint::Color(color) => vger.color_paint(*color),
+            Paint::Color(color) => renderer.color_paint(*color),
             Paint::Gradient {
                 start,
                 end,
                 inner_color,
                 outer_color,
-            } => vger.linear_gradient(*start, *end, *inner_color, *outer_color, 0.0),
+            } => renderer.linear_gradient(*start, *end, *inner_color, *outer_color, 0.0),
         }
     }
 }
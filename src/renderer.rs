@@ -0,0 +1,232 @@
+use crate::*;
+
+/// Refers to a paint created by a `Renderer` for the current frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PaintIndex {
+    pub index: usize,
+}
+
+/// Drawing interface used by `View::draw`.
+///
+/// When running in a window, this draws using vger. `Recorder` implements
+/// it to capture a display list instead.
+pub trait Renderer: TextMeasurer {
+    /// Saves the current transform and scissor.
+    fn save(&mut self);
+
+    /// Restores the last saved transform and scissor.
+    fn restore(&mut self);
+
+    /// Translates subsequent drawing.
+    fn translate(&mut self, offset: LocalOffset);
+
+    /// Scales subsequent drawing.
+    fn scale(&mut self, scale: LocalOffset);
+
+    /// Returns the transform from local to window coordinates.
+    fn current_transform(&self) -> LocalToWorld;
+
+    /// Clips subsequent drawing to a rectangle.
+    fn scissor(&mut self, rect: LocalRect);
+
+    /// Creates a solid color paint.
+    fn color_paint(&mut self, color: Color) -> PaintIndex;
+
+    /// Creates a linear gradient paint.
+    fn linear_gradient(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    ) -> PaintIndex;
+
+    /// Fills a rectangle with rounded corners.
+    fn fill_rect(&mut self, rect: LocalRect, radius: f32, paint: PaintIndex);
+
+    /// Fills a circle.
+    fn fill_circle(&mut self, center: LocalPoint, radius: f32, paint: PaintIndex);
+
+    /// Strokes a rectangle with rounded corners.
+    fn stroke_rect(
+        &mut self,
+        min: LocalPoint,
+        max: LocalPoint,
+        radius: f32,
+        width: f32,
+        paint: PaintIndex,
+    );
+
+    /// Strokes an arc. `rotation` is the direction of the arc's center
+    /// and `aperture` is half the arc's angle.
+    fn stroke_arc(
+        &mut self,
+        center: LocalPoint,
+        radius: f32,
+        width: f32,
+        rotation: f32,
+        aperture: f32,
+        paint: PaintIndex,
+    );
+
+    /// Strokes a line segment.
+    fn stroke_segment(&mut self, a: LocalPoint, b: LocalPoint, width: f32, paint: PaintIndex);
+
+    /// Strokes a quadratic bezier curve.
+    fn stroke_bezier(
+        &mut self,
+        a: LocalPoint,
+        b: LocalPoint,
+        c: LocalPoint,
+        width: f32,
+        paint: PaintIndex,
+    );
+
+    /// Draws text. Lines go down from the origin.
+    fn text(&mut self, text: &str, size: u32, color: Color, max_width: Option<f32>);
+}
+
+/// Draws with vger.
+pub(crate) struct VgerRenderer<'a> {
+    vger: &'a mut Vger,
+
+    /// Paints created this frame.
+    paints: Vec<vger::PaintIndex>,
+}
+
+impl<'a> VgerRenderer<'a> {
+    pub fn new(vger: &'a mut Vger) -> Self {
+        Self {
+            vger,
+            paints: vec![],
+        }
+    }
+
+    fn add_paint(&mut self, paint: vger::PaintIndex) -> PaintIndex {
+        self.paints.push(paint);
+        PaintIndex {
+            index: self.paints.len() - 1,
+        }
+    }
+}
+
+impl<'a> TextMeasurer for VgerRenderer<'a> {
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        self.vger.text_bounds(text, size, max_width)
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        self.vger.glyph_positions(text, size, max_width)
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        self.vger.line_metrics(text, size, max_width)
+    }
+}
+
+impl<'a> Renderer for VgerRenderer<'a> {
+    fn save(&mut self) {
+        self.vger.save()
+    }
+
+    fn restore(&mut self) {
+        self.vger.restore()
+    }
+
+    fn translate(&mut self, offset: LocalOffset) {
+        self.vger.translate(offset)
+    }
+
+    fn scale(&mut self, scale: LocalOffset) {
+        self.vger.scale(scale)
+    }
+
+    fn current_transform(&self) -> LocalToWorld {
+        self.vger.current_transform()
+    }
+
+    fn scissor(&mut self, rect: LocalRect) {
+        self.vger.scissor(rect)
+    }
+
+    fn color_paint(&mut self, color: Color) -> PaintIndex {
+        let paint = self.vger.color_paint(color);
+        self.add_paint(paint)
+    }
+
+    fn linear_gradient(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    ) -> PaintIndex {
+        let paint = self
+            .vger
+            .linear_gradient(start, end, inner_color, outer_color, glow);
+        self.add_paint(paint)
+    }
+
+    fn fill_rect(&mut self, rect: LocalRect, radius: f32, paint: PaintIndex) {
+        self.vger.fill_rect(rect, radius, self.paints[paint.index])
+    }
+
+    fn fill_circle(&mut self, center: LocalPoint, radius: f32, paint: PaintIndex) {
+        self.vger
+            .fill_circle(center, radius, self.paints[paint.index])
+    }
+
+    fn stroke_rect(
+        &mut self,
+        min: LocalPoint,
+        max: LocalPoint,
+        radius: f32,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        self.vger
+            .stroke_rect(min, max, radius, width, self.paints[paint.index])
+    }
+
+    fn stroke_arc(
+        &mut self,
+        center: LocalPoint,
+        radius: f32,
+        width: f32,
+        rotation: f32,
+        aperture: f32,
+        paint: PaintIndex,
+    ) {
+        self.vger.stroke_arc(
+            center,
+            radius,
+            width,
+            rotation,
+            aperture,
+            self.paints[paint.index],
+        )
+    }
+
+    fn stroke_segment(&mut self, a: LocalPoint, b: LocalPoint, width: f32, paint: PaintIndex) {
+        self.vger
+            .stroke_segment(a, b, width, self.paints[paint.index])
+    }
+
+    fn stroke_bezier(
+        &mut self,
+        a: LocalPoint,
+        b: LocalPoint,
+        c: LocalPoint,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        self.vger
+            .stroke_bezier(a, b, c, width, self.paints[paint.index])
+    }
+
+    fn text(&mut self, text: &str, size: u32, color: Color, max_width: Option<f32>) {
+        self.vger.text(text, size, color, max_width)
+    }
+}
@@ -0,0 +1,324 @@
+use crate::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// A key press with modifiers which runs a command, such as Cmd+Shift+Z.
+///
+/// Parse one from a string, where `Primary` is command on macOS and
+/// control elsewhere:
+///
+/// ```
+/// # use rui::*;
+/// let redo: Shortcut = "Primary+Shift+Z".parse().unwrap();
+/// let refresh: Shortcut = "F5".parse().unwrap();
+/// assert_eq!(refresh.key, Key::F5);
+/// ```
+///
+/// Character keys are stored as typed without shift on a US layout, so
+/// `"Cmd+Shift+1"` and `"Cmd+!"` are the same shortcut, as are
+/// `"Ctrl+Plus"` and `"Ctrl+Shift+="`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub key: Key,
+    pub modifiers: KeyboardModifiers,
+}
+
+/// Why a string couldn't be parsed as a `Shortcut`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseShortcutError(String);
+
+impl fmt::Display for ParseShortcutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid shortcut: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseShortcutError {}
+
+/// Characters typed with shift, and the key typed without it.
+const SHIFTED: [(char, char); 21] = [
+    ('!', '1'),
+    ('@', '2'),
+    ('#', '3'),
+    ('$', '4'),
+    ('%', '5'),
+    ('^', '6'),
+    ('&', '7'),
+    ('*', '8'),
+    ('(', '9'),
+    (')', '0'),
+    (':', ';'),
+    ('>', '.'),
+    ('<', ','),
+    ('_', '-'),
+    ('?', '/'),
+    ('~', '`'),
+    ('+', '='),
+    ('{', '['),
+    ('}', ']'),
+    ('|', '\\'),
+    ('"', '\''),
+];
+
+/// Names of keys which aren't a single character.
+const KEY_NAMES: [(&str, Key); 26] = [
+    ("Enter", Key::Enter),
+    ("Tab", Key::Tab),
+    ("Space", Key::Space),
+    ("Down", Key::ArrowDown),
+    ("Left", Key::ArrowLeft),
+    ("Right", Key::ArrowRight),
+    ("Up", Key::ArrowUp),
+    ("End", Key::End),
+    ("Home", Key::Home),
+    ("PageDown", Key::PageDown),
+    ("PageUp", Key::PageUp),
+    ("Backspace", Key::Backspace),
+    ("Delete", Key::Delete),
+    ("Escape", Key::Escape),
+    ("F1", Key::F1),
+    ("F2", Key::F2),
+    ("F3", Key::F3),
+    ("F4", Key::F4),
+    ("F5", Key::F5),
+    ("F6", Key::F6),
+    ("F7", Key::F7),
+    ("F8", Key::F8),
+    ("F9", Key::F9),
+    ("F10", Key::F10),
+    ("F11", Key::F11),
+    ("F12", Key::F12),
+];
+
+/// Other names accepted by the parser.
+const KEY_ALIASES: [(&str, Key); 5] = [
+    ("Return", Key::Enter),
+    ("Esc", Key::Escape),
+    ("Del", Key::Delete),
+    ("Plus", Key::Character('+')),
+    ("Minus", Key::Character('-')),
+];
+
+impl Shortcut {
+    /// A character which is typed with shift implies shift, so the
+    /// keypad's `+` is the same as Shift+=.
+    pub fn new(key: Key, modifiers: KeyboardModifiers) -> Self {
+        let (key, shifted) = Self::unshifted(key);
+        Self {
+            key,
+            modifiers: KeyboardModifiers {
+                shift: modifiers.shift || shifted,
+                ..modifiers
+            },
+        }
+    }
+
+    /// The key without shift, and whether shift was needed to type it.
+    fn unshifted(key: Key) -> (Key, bool) {
+        match key {
+            Key::Character(c) if c.is_ascii_uppercase() => {
+                (Key::Character(c.to_ascii_lowercase()), false)
+            }
+            Key::Character(c) => match SHIFTED.iter().find(|(shifted, _)| *shifted == c) {
+                Some((_, base)) => (Key::Character(*base), true),
+                None => (key, false),
+            },
+            _ => (key, false),
+        }
+    }
+
+    /// Is this the shortcut for a key press?
+    pub fn matches(&self, key: Key, modifiers: KeyboardModifiers) -> bool {
+        *self == Self::new(key, modifiers)
+    }
+
+    fn parse_key(name: &str) -> Option<(Key, bool)> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if c.is_ascii_graphic() {
+                return Some(Self::unshifted(Key::Character(c)));
+            }
+        }
+        KEY_NAMES
+            .iter()
+            .chain(KEY_ALIASES.iter())
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, key)| Self::unshifted(*key))
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = ParseShortcutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let error = || ParseShortcutError(s.into());
+
+        // The key can itself be a plus.
+        let (mods, key) = if s == "+" {
+            ("", "+")
+        } else if let Some(mods) = s.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            s.rsplit_once('+').unwrap_or(("", s))
+        };
+
+        let (key, shift) = Self::parse_key(key.trim()).ok_or_else(error)?;
+        let mut modifiers = KeyboardModifiers {
+            shift,
+            ..Default::default()
+        };
+        for name in mods.split('+').map(str::trim).filter(|m| !m.is_empty()) {
+            match name.to_ascii_lowercase().as_str() {
+                "shift" => modifiers.shift = true,
+                "ctrl" | "control" => modifiers.control = true,
+                "alt" | "option" | "opt" => modifiers.alt = true,
+                "cmd" | "command" | "super" | "meta" | "win" => modifiers.command = true,
+                "primary" | "cmdorctrl" | "commandorcontrol" | "mod" => {
+                    let primary = KeyboardModifiers::primary();
+                    modifiers.command |= primary.command;
+                    modifiers.control |= primary.control;
+                }
+                _ => return Err(error()),
+            }
+        }
+
+        Ok(Self { key, modifiers })
+    }
+}
+
+impl fmt::Display for Shortcut {
+    /// Writes the shortcut in a form `parse` reads, such as `Ctrl+Shift+Z`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Shift+= is written as Plus.
+        let plus = self.key == Key::Character('=') && self.modifiers.shift;
+        let mods = [
+            (self.modifiers.control, "Ctrl+"),
+            (self.modifiers.alt, "Alt+"),
+            (self.modifiers.shift && !plus, "Shift+"),
+            (self.modifiers.command, "Cmd+"),
+        ];
+        for (down, name) in mods {
+            if down {
+                f.write_str(name)?;
+            }
+        }
+        match self.key {
+            _ if plus => f.write_str("Plus"),
+            Key::Character(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            key => match KEY_NAMES
+                .iter()
+                .chain(KEY_ALIASES.iter())
+                .find(|(_, k)| *k == key)
+            {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{:?}", key),
+            },
+        }
+    }
+}
+
+impl From<HotKey> for Shortcut {
+    /// The letter with the primary modifier.
+    fn from(key: HotKey) -> Self {
+        Self {
+            key: Key::Character(key.character()),
+            modifiers: KeyboardModifiers::primary(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn mods(control: bool, alt: bool, shift: bool, command: bool) -> KeyboardModifiers {
+        KeyboardModifiers {
+            shift,
+            control,
+            alt,
+            command,
+        }
+    }
+
+    #[test]
+    fn test_parse_shortcut() {
+        let s: Shortcut = "Cmd+Shift+Z".parse().unwrap();
+        assert_eq!(s.key, Key::Character('z'));
+        assert_eq!(s.modifiers, mods(false, false, true, true));
+
+        assert_eq!(
+            "ctrl + 1".parse::<Shortcut>().unwrap(),
+            Shortcut::new(Key::Character('1'), mods(true, false, false, false))
+        );
+        assert_eq!("Cmd+!".parse::<Shortcut>(), "Cmd+Shift+1".parse());
+        assert_eq!("F5".parse::<Shortcut>().unwrap().key, Key::F5);
+        assert_eq!("Alt+Up".parse::<Shortcut>().unwrap().key, Key::ArrowUp);
+        assert_eq!(
+            "Primary+S".parse::<Shortcut>().unwrap(),
+            Shortcut::from(HotKey::KeyS)
+        );
+        assert_eq!(
+            "Ctrl++".parse::<Shortcut>().unwrap(),
+            "Ctrl+Plus".parse().unwrap()
+        );
+        assert_eq!(
+            "Cmd+,".parse::<Shortcut>().unwrap().key,
+            Key::Character(',')
+        );
+
+        assert!("Cmd+".parse::<Shortcut>().is_err());
+        assert!("Hyper+A".parse::<Shortcut>().is_err());
+        assert!("Cmd+Foo".parse::<Shortcut>().is_err());
+    }
+
+    #[test]
+    fn test_shortcut_display() {
+        for s in [
+            "Ctrl+Alt+Shift+Cmd+Z",
+            "F12",
+            "Shift+PageDown",
+            "Ctrl+Plus",
+            "Cmd+/",
+        ] {
+            let shortcut: Shortcut = s.parse().unwrap();
+            assert_eq!(shortcut.to_string(), s);
+        }
+        assert_eq!(
+            "cmd+shift+?".parse::<Shortcut>().unwrap().to_string(),
+            "Shift+Cmd+/"
+        );
+    }
+
+    #[test]
+    fn test_shortcut_matches() {
+        let s: Shortcut = "Ctrl+Shift+Z".parse().unwrap();
+        assert!(s.matches(Key::Character('Z'), mods(true, false, true, false)));
+        assert!(!s.matches(Key::Character('z'), mods(true, false, false, false)));
+
+        // Typed with shift, the 1 key gives a !.
+        let s: Shortcut = "Ctrl+Shift+1".parse().unwrap();
+        assert!(s.matches(Key::Character('!'), mods(true, false, true, false)));
+
+        // Plus is shift and = on the main keyboard, or on its own key.
+        let s: Shortcut = "Ctrl+Plus".parse().unwrap();
+        assert!(s.matches(Key::Character('+'), mods(true, false, true, false)));
+        assert!(s.matches(Key::Character('+'), mods(true, false, false, false)));
+        assert!(!s.matches(Key::Character('='), mods(true, false, false, false)));
+        let s: Shortcut = "Ctrl+=".parse().unwrap();
+        assert!(s.matches(Key::Character('='), mods(true, false, false, false)));
+
+        let s: Shortcut = "Cmd+[".parse().unwrap();
+        assert!(s.matches(Key::Character('['), mods(false, false, false, true)));
+        let s: Shortcut = "Cmd+Shift+]".parse().unwrap();
+        assert!(s.matches(Key::Character('}'), mods(false, false, true, true)));
+        let s: Shortcut = "Cmd+\\".parse().unwrap();
+        assert!(s.matches(Key::Character('\\'), mods(false, false, false, true)));
+        let s: Shortcut = "Ctrl+|".parse().unwrap();
+        assert!(s.matches(Key::Character('|'), mods(true, false, true, false)));
+        let s: Shortcut = "Ctrl+'".parse().unwrap();
+        assert!(s.matches(Key::Character('\''), mods(true, false, false, false)));
+        assert!(!s.matches(Key::Character('"'), mods(true, false, true, false)));
+    }
+}
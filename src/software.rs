@@ -0,0 +1,621 @@
+use crate::*;
+use fontdue::layout::{CoordinateSystem, GlyphPosition, Layout, LayoutSettings, TextStyle};
+use std::path::Path;
+use tiny_skia as sk;
+
+/// Font used when no other is given, see `fonts/README.md`.
+const DEFAULT_FONT: &[u8] = include_bytes!("fonts/Cantarell-Regular.ttf");
+
+/// Measures text using a font loaded with fontdue, for use
+/// without vger.
+pub struct FontText {
+    font: fontdue::Font,
+    layout: Layout,
+}
+
+impl FontText {
+    /// Loads a TrueType or OpenType font.
+    pub fn new(data: &[u8]) -> Result<Self, &'static str> {
+        Ok(Self {
+            font: fontdue::Font::from_bytes(data, fontdue::FontSettings::default())?,
+            layout: Layout::new(CoordinateSystem::PositiveYDown),
+        })
+    }
+
+    /// Cantarell, which is bundled with the `software` feature. It only
+    /// covers Latin text.
+    pub fn cantarell() -> Self {
+        Self::new(DEFAULT_FONT).expect("bundled font is valid")
+    }
+
+    /// Lays out glyphs with the top of the first line at y = 0 and y going down.
+    fn layout(&mut self, text: &str, size: f32, max_width: Option<f32>) -> &Layout {
+        self.layout.reset(&LayoutSettings {
+            max_width,
+            ..LayoutSettings::default()
+        });
+        self.layout
+            .append(&[&self.font], &TextStyle::new(text, size, 0));
+        &self.layout
+    }
+}
+
+fn glyph_rect(glyph: &GlyphPosition) -> LocalRect {
+    LocalRect::new(
+        [glyph.x, -glyph.y - glyph.height as f32].into(),
+        [glyph.width as f32, glyph.height as f32].into(),
+    )
+}
+
+impl TextMeasurer for FontText {
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        let layout = self.layout(text, size as f32, max_width);
+        let width = layout
+            .glyphs()
+            .iter()
+            .map(|g| g.x + g.width as f32)
+            .fold(0.0, f32::max);
+        let height = layout.height();
+        LocalRect::new([0.0, -height].into(), [width, height].into())
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        let layout = self.layout(text, size as f32, max_width);
+        layout.glyphs().iter().map(glyph_rect).collect()
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        let layout = self.layout(text, size as f32, max_width);
+        let glyphs = layout.glyphs();
+        match layout.lines() {
+            Some(lines) => lines
+                .iter()
+                .map(|line| {
+                    let top = line.baseline_y - line.max_ascent;
+                    let height = line.max_ascent - line.min_descent + line.max_line_gap;
+                    let width = glyphs[line.glyph_start..=line.glyph_end]
+                        .iter()
+                        .map(|g| g.x + g.width as f32)
+                        .fold(0.0, f32::max);
+                    LineMetrics {
+                        glyph_start: line.glyph_start,
+                        glyph_end: line.glyph_end + 1,
+                        bounds: LocalRect::new([0.0, -top - height].into(), [width, height].into()),
+                    }
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+#[derive(Clone)]
+struct SoftwareState {
+    xform: LocalToWorld,
+
+    /// Scissor rectangle in pixels.
+    clip: Option<sk::Rect>,
+}
+
+/// A `Renderer` which rasterizes on the CPU into an RGBA image, for
+/// machines without a GPU.
+///
+/// Text is drawn with `FontText::cantarell` unless another font is set
+/// with `font`. Gradients are drawn without vger's glow.
+pub struct SoftwareRenderer {
+    pixmap: sk::Pixmap,
+    scale: f32,
+    state: SoftwareState,
+    stack: Vec<SoftwareState>,
+    paints: Vec<RecordedPaint>,
+    mask: Option<(sk::Rect, sk::Mask)>,
+    font: FontText,
+}
+
+impl SoftwareRenderer {
+    /// Creates a renderer for a window of `size` points, with `scale`
+    /// pixels per point. The image starts out black, as in a window.
+    pub fn new(size: impl Into<LocalSize>, scale: f32) -> Self {
+        let size = size.into();
+        let width = (size.width * scale).ceil().max(1.0) as u32;
+        let height = (size.height * scale).ceil().max(1.0) as u32;
+        let mut pixmap = sk::Pixmap::new(width, height).expect("invalid image size");
+        pixmap.fill(sk::Color::BLACK);
+        Self {
+            pixmap,
+            scale,
+            state: SoftwareState {
+                xform: LocalToWorld::identity(),
+                clip: None,
+            },
+            stack: vec![],
+            paints: vec![],
+            mask: None,
+            font: FontText::cantarell(),
+        }
+    }
+
+    /// Sets the font used to measure and draw text.
+    pub fn font(self, font: FontText) -> Self {
+        Self { font, ..self }
+    }
+
+    /// Width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        self.pixmap.width()
+    }
+
+    /// Height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        self.pixmap.height()
+    }
+
+    /// Returns the image as RGBA bytes, row by row from the top.
+    pub fn rgba(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.pixmap.data().len());
+        for pixel in self.pixmap.pixels() {
+            let c = pixel.demultiply();
+            data.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+        }
+        data
+    }
+
+    /// Encodes the image as a PNG.
+    pub fn encode_png(&self) -> std::io::Result<Vec<u8>> {
+        self.pixmap.encode_png().map_err(std::io::Error::other)
+    }
+
+    /// Clears the image to black and resets the transform, ready to draw
+    /// another frame.
+    pub fn clear(&mut self) {
+        self.pixmap.fill(sk::Color::BLACK);
+        self.state = SoftwareState {
+            xform: LocalToWorld::identity(),
+            clip: None,
+        };
+        self.stack.clear();
+        self.paints.clear();
+    }
+
+    /// Transform from local coordinates to pixels, which have y going down.
+    fn transform(&self) -> sk::Transform {
+        let m = self.state.xform;
+        let window_height = self.pixmap.height() as f32 / self.scale;
+        sk::Transform::from_row(
+            self.scale,
+            0.0,
+            0.0,
+            -self.scale,
+            0.0,
+            self.scale * window_height,
+        )
+        .pre_concat(sk::Transform::from_row(
+            m.m11, m.m12, m.m21, m.m22, m.m31, m.m32,
+        ))
+    }
+
+    fn update_mask(&mut self) {
+        let clip = match self.state.clip {
+            Some(clip) => clip,
+            None => return,
+        };
+        if let Some((rect, _)) = &self.mask {
+            if *rect == clip {
+                return;
+            }
+        }
+        let mut mask = sk::Mask::new(self.pixmap.width(), self.pixmap.height()).unwrap();
+        mask.fill_path(
+            &sk::PathBuilder::from_rect(clip),
+            sk::FillRule::Winding,
+            false,
+            sk::Transform::identity(),
+        );
+        self.mask = Some((clip, mask));
+    }
+
+    fn sk_paint(&self, paint: PaintIndex) -> sk::Paint<'static> {
+        let mut sk_paint = sk::Paint {
+            anti_alias: true,
+            ..sk::Paint::default()
+        };
+        match &self.paints[paint.index] {
+            RecordedPaint::Color(color) => sk_paint.set_color(sk_color(*color)),
+            // tiny-skia has nothing like vger's glow, so it's ignored.
+            RecordedPaint::Gradient {
+                start,
+                end,
+                inner_color,
+                outer_color,
+                ..
+            } => {
+                match sk::LinearGradient::new(
+                    sk::Point::from_xy(start.x, start.y),
+                    sk::Point::from_xy(end.x, end.y),
+                    vec![
+                        sk::GradientStop::new(0.0, sk_color(*inner_color)),
+                        sk::GradientStop::new(1.0, sk_color(*outer_color)),
+                    ],
+                    sk::SpreadMode::Pad,
+                    sk::Transform::identity(),
+                ) {
+                    Some(shader) => sk_paint.shader = shader,
+                    None => sk_paint.set_color(sk_color(*inner_color)),
+                }
+            }
+        }
+        sk_paint
+    }
+
+    fn fill(&mut self, path: Option<sk::Path>, paint: PaintIndex) {
+        if let Some(path) = path {
+            let sk_paint = self.sk_paint(paint);
+            let transform = self.transform();
+            self.update_mask();
+            let mask = self.state.clip.and(self.mask.as_ref()).map(|(_, m)| m);
+            self.pixmap
+                .fill_path(&path, &sk_paint, sk::FillRule::Winding, transform, mask);
+        }
+    }
+
+    fn stroke(&mut self, path: Option<sk::Path>, width: f32, cap: sk::LineCap, paint: PaintIndex) {
+        if let Some(path) = path {
+            let sk_paint = self.sk_paint(paint);
+            let stroke = sk::Stroke {
+                width,
+                line_cap: cap,
+                ..sk::Stroke::default()
+            };
+            let transform = self.transform();
+            self.update_mask();
+            let mask = self.state.clip.and(self.mask.as_ref()).map(|(_, m)| m);
+            self.pixmap
+                .stroke_path(&path, &sk_paint, &stroke, transform, mask);
+        }
+    }
+
+    /// Blends a glyph's coverage into the image at a pixel position.
+    fn blit_glyph(&mut self, x: i32, y: i32, width: usize, coverage: &[u8], color: Color) {
+        let (w, h) = (self.pixmap.width() as i32, self.pixmap.height() as i32);
+        let clip = self
+            .state
+            .clip
+            .unwrap_or_else(|| sk::Rect::from_xywh(0.0, 0.0, w as f32, h as f32).unwrap());
+        let pixels = self.pixmap.pixels_mut();
+        for (i, c) in coverage.iter().enumerate() {
+            let px = x + (i % width) as i32;
+            let py = y + (i / width) as i32;
+            if px < 0 || py < 0 || px >= w || py >= h || *c == 0 {
+                continue;
+            }
+            let (fx, fy) = (px as f32 + 0.5, py as f32 + 0.5);
+            if fx < clip.left() || fx > clip.right() || fy < clip.top() || fy > clip.bottom() {
+                continue;
+            }
+            let a = color.a * *c as f32 / 255.0;
+            let dst = &mut pixels[(py * w + px) as usize];
+            let blend = |src: f32, dst: u8| (src * a * 255.0 + dst as f32 * (1.0 - a)) as u8;
+            *dst = sk::PremultipliedColorU8::from_rgba(
+                blend(color.r, dst.red()),
+                blend(color.g, dst.green()),
+                blend(color.b, dst.blue()),
+                ((a * 255.0) + dst.alpha() as f32 * (1.0 - a)) as u8,
+            )
+            .unwrap_or(*dst);
+        }
+    }
+}
+
+fn sk_color(color: Color) -> sk::Color {
+    sk::Color::from_rgba(
+        color.r.clamp(0.0, 1.0),
+        color.g.clamp(0.0, 1.0),
+        color.b.clamp(0.0, 1.0),
+        color.a.clamp(0.0, 1.0),
+    )
+    .unwrap()
+}
+
+fn rounded_rect(min: LocalPoint, max: LocalPoint, radius: f32) -> Option<sk::Path> {
+    let r = radius.min((max.x - min.x) / 2.0).min((max.y - min.y) / 2.0);
+    if r <= 0.0 {
+        return Some(sk::PathBuilder::from_rect(sk::Rect::from_ltrb(
+            min.x, min.y, max.x, max.y,
+        )?));
+    }
+
+    // Control point distance for approximating a quarter circle with a cubic.
+    let k = r * 0.552_284_8;
+    let mut pb = sk::PathBuilder::new();
+    pb.move_to(min.x + r, min.y);
+    pb.line_to(max.x - r, min.y);
+    pb.cubic_to(max.x - r + k, min.y, max.x, min.y + r - k, max.x, min.y + r);
+    pb.line_to(max.x, max.y - r);
+    pb.cubic_to(max.x, max.y - r + k, max.x - r + k, max.y, max.x - r, max.y);
+    pb.line_to(min.x + r, max.y);
+    pb.cubic_to(min.x + r - k, max.y, min.x, max.y - r + k, min.x, max.y - r);
+    pb.line_to(min.x, min.y + r);
+    pb.cubic_to(min.x, min.y + r - k, min.x + r - k, min.y, min.x + r, min.y);
+    pb.close();
+    pb.finish()
+}
+
+impl TextMeasurer for SoftwareRenderer {
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        self.font.text_bounds(text, size, max_width)
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        self.font.glyph_positions(text, size, max_width)
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        self.font.line_metrics(text, size, max_width)
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn save(&mut self) {
+        self.stack.push(self.state.clone())
+    }
+
+    fn restore(&mut self) {
+        if let Some(state) = self.stack.pop() {
+            self.state = state;
+        }
+    }
+
+    fn translate(&mut self, offset: LocalOffset) {
+        self.state.xform = self.state.xform.pre_translate(offset);
+    }
+
+    fn scale(&mut self, scale: LocalOffset) {
+        self.state.xform = self.state.xform.pre_scale(scale.x, scale.y);
+    }
+
+    fn current_transform(&self) -> LocalToWorld {
+        self.state.xform
+    }
+
+    fn scissor(&mut self, rect: LocalRect) {
+        let rect = sk::Rect::from_ltrb(rect.min_x(), rect.min_y(), rect.max_x(), rect.max_y())
+            .and_then(|r| r.transform(self.transform()));
+        self.state.clip = match (rect, self.state.clip) {
+            (Some(rect), Some(clip)) => rect
+                .intersect(&clip)
+                .or_else(|| sk::Rect::from_xywh(0.0, 0.0, 0.0, 0.0)),
+            (rect, _) => rect,
+        };
+    }
+
+    fn color_paint(&mut self, color: Color) -> PaintIndex {
+        self.paints.push(RecordedPaint::Color(color));
+        PaintIndex {
+            index: self.paints.len() - 1,
+        }
+    }
+
+    fn linear_gradient(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    ) -> PaintIndex {
+        self.paints.push(RecordedPaint::Gradient {
+            start,
+            end,
+            inner_color,
+            outer_color,
+            glow,
+        });
+        PaintIndex {
+            index: self.paints.len() - 1,
+        }
+    }
+
+    fn fill_rect(&mut self, rect: LocalRect, radius: f32, paint: PaintIndex) {
+        self.fill(rounded_rect(rect.min(), rect.max(), radius), paint)
+    }
+
+    fn fill_circle(&mut self, center: LocalPoint, radius: f32, paint: PaintIndex) {
+        self.fill(
+            sk::PathBuilder::from_circle(center.x, center.y, radius),
+            paint,
+        )
+    }
+
+    fn stroke_rect(
+        &mut self,
+        min: LocalPoint,
+        max: LocalPoint,
+        radius: f32,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        self.stroke(
+            rounded_rect(min, max, radius),
+            width,
+            sk::LineCap::Butt,
+            paint,
+        )
+    }
+
+    fn stroke_arc(
+        &mut self,
+        center: LocalPoint,
+        radius: f32,
+        width: f32,
+        rotation: f32,
+        aperture: f32,
+        paint: PaintIndex,
+    ) {
+        let full = aperture >= std::f32::consts::PI;
+        let aperture = aperture.min(std::f32::consts::PI);
+        let n = ((aperture * 32.0).ceil() as usize).max(2);
+        let mut pb = sk::PathBuilder::new();
+        for i in 0..=n {
+            let theta = rotation - aperture + 2.0 * aperture * i as f32 / n as f32;
+            let p = center + LocalOffset::new(theta.cos(), theta.sin()) * radius;
+            if i == 0 {
+                pb.move_to(p.x, p.y);
+            } else {
+                pb.line_to(p.x, p.y);
+            }
+        }
+        if full {
+            pb.close();
+        }
+        self.stroke(pb.finish(), width, sk::LineCap::Round, paint)
+    }
+
+    fn stroke_segment(&mut self, a: LocalPoint, b: LocalPoint, width: f32, paint: PaintIndex) {
+        let mut pb = sk::PathBuilder::new();
+        pb.move_to(a.x, a.y);
+        pb.line_to(b.x, b.y);
+        self.stroke(pb.finish(), width, sk::LineCap::Round, paint)
+    }
+
+    fn stroke_bezier(
+        &mut self,
+        a: LocalPoint,
+        b: LocalPoint,
+        c: LocalPoint,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        let mut pb = sk::PathBuilder::new();
+        pb.move_to(a.x, a.y);
+        pb.quad_to(b.x, b.y, c.x, c.y);
+        self.stroke(pb.finish(), width, sk::LineCap::Round, paint)
+    }
+
+    fn text(&mut self, text: &str, size: u32, color: Color, max_width: Option<f32>) {
+        let transform = self.transform();
+
+        // Lay out at the pixel size so glyphs are rasterized sharply.
+        let pixel_scale = transform.sx.abs();
+        let mut origin = sk::Point::zero();
+        transform.map_point(&mut origin);
+
+        let glyphs = self
+            .font
+            .layout(
+                text,
+                size as f32 * pixel_scale,
+                max_width.map(|w| w * pixel_scale),
+            )
+            .glyphs()
+            .clone();
+        for glyph in glyphs {
+            if glyph.width == 0 || glyph.height == 0 {
+                continue;
+            }
+            let (_, coverage) = self.font.font.rasterize_config(glyph.key);
+            self.blit_glyph(
+                (origin.x + glyph.x).round() as i32,
+                (origin.y + glyph.y).round() as i32,
+                glyph.width,
+                &coverage,
+                color,
+            );
+        }
+    }
+}
+
+/// Lays out and draws a view with a `SoftwareRenderer`.
+pub fn render_software(view: &impl View, renderer: &mut SoftwareRenderer) {
+    let window_size = [
+        renderer.width() as f32 / renderer.scale,
+        renderer.height() as f32 / renderer.scale,
+    ]
+    .into();
+    let mut cx = Context::new();
+    let mut access_nodes = vec![];
+
+    // Some views (such as sliders) measure themselves while drawing,
+    // so draw twice to let them settle.
+    for _ in 0..2 {
+        cx.update_with(view, renderer, &mut access_nodes, window_size);
+        renderer.clear();
+        cx.enable_dirty = false;
+        cx.draw_root(view, renderer);
+        cx.enable_dirty = true;
+    }
+}
+
+/// Renders a view to a PNG file without a GPU. `size` is in points and
+/// `scale` is the number of pixels per point. Text is drawn with
+/// `FontText::cantarell`; use `SoftwareRenderer` to pick another font.
+pub fn render_to_png(
+    view: &impl View,
+    size: impl Into<LocalSize>,
+    scale: f32,
+    path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut renderer = SoftwareRenderer::new(size, scale);
+    render_software(view, &mut renderer);
+    std::fs::write(path, renderer.encode_png()?)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn pixel(renderer: &SoftwareRenderer, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * renderer.width() + x) * 4) as usize;
+        let data = renderer.rgba();
+        [data[i], data[i + 1], data[i + 2], data[i + 3]]
+    }
+
+    #[test]
+    fn test_software_rect() {
+        let ui = rectangle().color(RED_HIGHLIGHT).size([20.0, 20.0]);
+        let mut renderer = SoftwareRenderer::new([100.0, 100.0], 2.0);
+        render_software(&ui, &mut renderer);
+
+        assert_eq!((renderer.width(), renderer.height()), (200, 200));
+        assert_eq!(pixel(&renderer, 100, 100), [255, 0, 98, 255]);
+        assert_eq!(pixel(&renderer, 10, 10), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_software_flips_y() {
+        // Moves the rectangle to the top right, since y goes up.
+        let ui = rectangle()
+            .color(RED_HIGHLIGHT)
+            .size([10.0, 10.0])
+            .offset([20.0, 20.0]);
+        let mut renderer = SoftwareRenderer::new([50.0, 50.0], 1.0);
+        render_software(&ui, &mut renderer);
+
+        assert_eq!(pixel(&renderer, 45, 5), [255, 0, 98, 255]);
+        assert_eq!(pixel(&renderer, 45, 45), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_render_to_png() {
+        let path = std::env::temp_dir().join("rui_test_render_to_png.png");
+        let ui = state(|| 0.5, |s, _| hslider(s));
+        render_to_png(&ui, [200.0, 40.0], 1.0, &path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(&data[1..4], b"PNG");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_render_to_png_text() {
+        let path = std::env::temp_dir().join("rui_test_render_to_png_text.png");
+        let ui = text("Hello").font_size(30);
+        render_to_png(&ui, [200.0, 100.0], 1.0, &path).unwrap();
+        let image = sk::Pixmap::load_png(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The text is centered, and nothing else is drawn.
+        let lit = |x: u32, y: u32| image.pixel(x, y).unwrap().red() > 128;
+        assert!((60..140).any(|x| (35..65).any(|y| lit(x, y))));
+        assert!(!(0..200).any(|x| (0..20).any(|y| lit(x, y))));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::*;
+use futures::task::ArcWake;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// Refers to a future started with `Context::spawn`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TaskHandle {
+    pub(crate) id: u64,
+}
+
+impl TaskHandle {
+    /// Drops the future. Does nothing if it already finished or was cancelled.
+    pub fn cancel(self, cx: &mut Context) {
+        cx.tasks.remove(&self.id);
+    }
+
+    /// Is the future still running?
+    pub fn is_active(self, cx: &Context) -> bool {
+        cx.tasks
+            .get(&self.id)
+            .is_some_and(|task| task.future.is_some())
+    }
+}
+
+/// A future which, when done, returns what to do with its result.
+pub(crate) type TaskFuture = Pin<Box<dyn Future<Output = Box<dyn FnOnce(&mut Context)>>>>;
+
+pub(crate) struct Task {
+    /// `None` once finished, or while being polled.
+    pub future: Option<TaskFuture>,
+
+    /// View which started the task with the `task` modifier. The task
+    /// is cancelled when the view goes away.
+    pub owner: Option<ViewId>,
+
+    /// Hash of the key the `task` modifier started the task with.
+    pub key: u64,
+}
+
+/// Ids of tasks which can make progress, shared with their wakers.
+pub(crate) type ReadyTasks = Arc<Mutex<Vec<u64>>>;
+
+/// Wakes the event loop from any thread.
+pub(crate) type WakeFn = Arc<dyn Fn() + Send + Sync>;
+
+pub(crate) struct TaskWaker {
+    pub id: u64,
+    pub ready: ReadyTasks,
+    pub wake: WakeFn,
+}
+
+impl ArcWake for TaskWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.ready.lock().unwrap().push(arc_self.id);
+        (arc_self.wake)();
+    }
+}
+
+/// Wakes the event loop without polling a task, for `on_receive`.
+pub(crate) struct EventLoopWaker {
+    pub wake: WakeFn,
+}
+
+impl ArcWake for EventLoopWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        (arc_self.wake)();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use futures::channel::oneshot;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_spawn() {
+        let mut h = TestHarness::new(state(|| 0, |_, _| EmptyView {}), [100.0, 100.0]);
+        let count = h.root_state::<i32>();
+
+        let (send, recv) = oneshot::channel();
+        let task =
+            h.cx.spawn(recv, move |cx, value| cx[count] = value.unwrap());
+        h.update();
+        assert!(task.is_active(&h.cx));
+
+        send.send(42).unwrap();
+        h.update();
+        assert_eq!(h.cx[count], 42);
+        assert!(!task.is_active(&h.cx));
+
+        // A cancelled future is dropped.
+        let (send, recv) = oneshot::channel::<i32>();
+        let task = h.cx.spawn(recv, move |cx, _| cx[count] = 0);
+        task.cancel(&mut h.cx);
+        assert!(send.is_canceled());
+        assert_eq!(h.cx[count], 42);
+    }
+
+    #[test]
+    fn test_task_modifier() {
+        // Receivers for each key, so the test decides when loading finishes.
+        let loads = Rc::new(RefCell::new(vec![]));
+        let loads2 = loads.clone();
+        let seen = Rc::new(Cell::new(0));
+        let seen2 = seen.clone();
+        let ui = state(
+            || (true, 1),
+            move |s, cx| {
+                let (show, key) = cx[s];
+                let loads = loads2.clone();
+                let seen = seen2.clone();
+                cond(
+                    show,
+                    state(
+                        || 0,
+                        move |result, cx| {
+                            seen.set(cx[result]);
+                            let loads = loads.clone();
+                            EmptyView {}.task(key, result, move |key| {
+                                let key = *key;
+                                let (send, recv) = oneshot::channel::<()>();
+                                loads.borrow_mut().push(send);
+                                async move {
+                                    let _ = recv.await;
+                                    key * 10
+                                }
+                            })
+                        },
+                    ),
+                    EmptyView {},
+                )
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<(bool, i32)>();
+        assert_eq!(loads.borrow().len(), 1);
+
+        loads.borrow_mut().remove(0).send(()).unwrap();
+        h.update();
+        assert_eq!(seen.get(), 10);
+
+        // Not started again once finished.
+        h.update();
+        assert_eq!(loads.borrow().len(), 0);
+
+        // A new key starts a new task, dropping the old one.
+        h.cx[s].1 = 2;
+        h.update();
+        assert_eq!(loads.borrow().len(), 1);
+        h.cx[s].1 = 3;
+        h.update();
+        assert_eq!(loads.borrow().len(), 2);
+        assert!(loads.borrow()[0].is_canceled());
+
+        // Removing the view cancels its task.
+        h.cx[s].0 = false;
+        h.update();
+        assert!(loads.borrow()[1].is_canceled());
+        assert!(h.cx.tasks.is_empty());
+    }
+}
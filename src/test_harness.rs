@@ -0,0 +1,690 @@
+use crate::*;
+use std::any::Any;
+use std::time::Duration;
+
+/// Runs a view tree without a window or GPU, for testing.
+///
+/// The harness owns a `Context` and a root view and runs the same
+/// update/layout/process cycle as the event loop. Positions passed
+/// to the helpers are in window coordinates (origin at the bottom left).
+///
+/// ```
+/// # use rui::*;
+/// let ui = state(|| 0, |count, _| button("+", move |cx| cx[count] += 1));
+/// let mut h = TestHarness::new(ui, [100.0, 100.0]);
+/// h.tap_at([50.0, 50.0]);
+/// let count = h.root_state::<i32>();
+/// assert_eq!(h.cx[count], 1);
+/// ```
+pub struct TestHarness<V> {
+    /// The UI state. Use it to read and write state through `StateHandle`s.
+    pub cx: Context,
+
+    /// Root of the view tree.
+    view: V,
+
+    /// Size of the fake window.
+    window_size: LocalSize,
+
+    /// Text measurement used during layout.
+    text: Box<dyn TextMeasurer>,
+
+    /// AccessKit nodes from the last update.
+    access_nodes: Vec<(accesskit::NodeId, accesskit::Node)>,
+
+    /// Actions which weren't handled by any view.
+    actions: Vec<Box<dyn Any>>,
+
+    /// Slots for touches started with `touch_begin`.
+    touches: TouchTracker,
+
+    /// Time as seen by animations. Only moves with `advance`.
+    clock: ManualClock,
+}
+
+impl<V: View> TestHarness<V> {
+    /// Creates a harness with a fake window of the given size and performs
+    /// an initial layout.
+    pub fn new(view: V, window_size: impl Into<LocalSize>) -> Self {
+        let mut harness = Self {
+            cx: Context::new(),
+            view,
+            window_size: window_size.into(),
+            text: Box::new(MonospaceText::default()),
+            access_nodes: vec![],
+            actions: vec![],
+            touches: TouchTracker::default(),
+            clock: ManualClock::default(),
+        };
+        harness.cx.set_clock(harness.clock.clone());
+        harness.cx.dirty = true;
+        harness.update();
+        harness
+    }
+
+    /// Replaces the text measurement used for layout. The default
+    /// is `MonospaceText`.
+    pub fn text_measurer(mut self, text: impl TextMeasurer + 'static) -> Self {
+        self.text = Box::new(text);
+        self.cx.invalidate_layout();
+        self.cx.dirty = true;
+        self.update();
+        self
+    }
+
+    /// Resizes the fake window and lays out again.
+    pub fn resize(&mut self, window_size: impl Into<LocalSize>) {
+        self.window_size = window_size.into();
+        self.cx.dirty = true;
+        self.update();
+    }
+
+    /// Runs timers and animations, cleans up state and lays out the view tree,
+    /// as the event loop does after each batch of events.
+    ///
+    /// Returns true if the UI needs to be redrawn.
+    pub fn update(&mut self) -> bool {
+        self.cx.update_with(
+            &self.view,
+            &mut *self.text,
+            &mut self.access_nodes,
+            self.window_size.cast_unit(),
+        )
+    }
+
+    /// Moves time forward and updates, as the event loop does for each
+    /// animation frame.
+    pub fn advance(&mut self, duration: Duration) -> bool {
+        self.clock.advance(duration);
+        self.update()
+    }
+
+    /// Draws the view tree, returning the recorded drawing commands.
+    pub fn draw(&mut self) -> DisplayList {
+        self.cx.repaint_all = true;
+        self.draw_dirty()
+    }
+
+    /// Draws only what changed since the last draw, as a window does.
+    /// Each dirty rectangle is scissored, cleared and drawn over.
+    pub fn draw_dirty(&mut self) -> DisplayList {
+        let mut recorder = Recorder::new();
+        std::mem::swap(&mut recorder.text, &mut self.text);
+
+        self.cx.enable_dirty = false;
+        self.cx.draw_dirty(&self.view, &mut recorder);
+        self.cx.enable_dirty = true;
+
+        std::mem::swap(&mut recorder.text, &mut self.text);
+        recorder.finish()
+    }
+
+    /// Sends an event to the view tree, then updates.
+    pub fn process(&mut self, event: &Event) {
+        self.cx.process_with(&self.view, event, &mut self.actions);
+        self.actions.retain(|action| !action.is::<()>());
+        self.update();
+    }
+
+    /// Clicks the left mouse button at a point.
+    pub fn tap_at(&mut self, position: impl Into<LocalPoint>) {
+        let position = position.into();
+        self.cx.mouse_button = Some(MouseButton::Left);
+        self.process(&Event::TouchBegin { id: 0, position });
+        self.cx.mouse_button = None;
+        self.process(&Event::TouchEnd { id: 0, position });
+    }
+
+    /// Drags the mouse from one point to another with the left button down.
+    pub fn drag(&mut self, from: impl Into<LocalPoint>, to: impl Into<LocalPoint>) {
+        let from = from.into();
+        let to = to.into();
+        self.cx.mouse_button = Some(MouseButton::Left);
+        self.process(&Event::TouchBegin {
+            id: 0,
+            position: from,
+        });
+        self.process(&Event::TouchMove {
+            id: 0,
+            position: to,
+            delta: to - from,
+        });
+        self.cx.mouse_button = None;
+        self.process(&Event::TouchEnd {
+            id: 0,
+            position: to,
+        });
+    }
+
+    /// Moves the mouse with no buttons pressed.
+    pub fn hover_at(&mut self, position: impl Into<LocalPoint>) {
+        self.process(&Event::PointerMove {
+            position: position.into(),
+        });
+    }
+
+    /// Moves the mouse out of the window.
+    pub fn pointer_leave(&mut self) {
+        self.process(&Event::PointerLeave);
+    }
+
+    /// Scrolls at a point. `delta` is how far the content should move.
+    pub fn scroll(&mut self, position: impl Into<LocalPoint>, delta: impl Into<LocalOffset>) {
+        self.process(&Event::Scroll {
+            position: position.into(),
+            delta: delta.into(),
+            phase: GestureState::Changed,
+            precise: true,
+        });
+    }
+
+    /// Starts a touch. `touch` identifies the finger, as a platform
+    /// touch ID would.
+    pub fn touch_begin(&mut self, touch: u64, position: impl Into<LocalPoint>) {
+        if let Some(event) = self.touches.begin(touch, position.into()) {
+            self.process(&event);
+        }
+    }
+
+    /// Moves a touch started with `touch_begin`.
+    pub fn touch_move(&mut self, touch: u64, position: impl Into<LocalPoint>) {
+        if let Some(event) = self.touches.moved(touch, position.into()) {
+            self.process(&event);
+        }
+    }
+
+    /// Ends a touch started with `touch_begin`.
+    pub fn touch_end(&mut self, touch: u64, position: impl Into<LocalPoint>) {
+        if let Some(event) = self.touches.end(touch, position.into()) {
+            self.process(&event);
+        }
+    }
+
+    /// Sends a key press.
+    pub fn send_key(&mut self, key: Key) {
+        self.process(&Event::Key(key));
+    }
+
+    /// Types a string one key at a time, sending each key press and
+    /// the text it enters. As in a window, no text is entered while
+    /// Ctrl or Command is held without Alt.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            let key = match c {
+                ' ' => Key::Space,
+                '\n' => Key::Enter,
+                '\t' => Key::Tab,
+                _ => Key::Character(c),
+            };
+            self.send_key(key);
+            if !c.is_control() && !self.cx.key_mods.shortcut() {
+                self.input_text(&c.to_string());
+            }
+        }
+    }
+
+    /// Sends text without key presses, as an input method does when
+    /// it commits.
+    pub fn input_text(&mut self, text: &str) {
+        self.process(&Event::TextInput(text.into()));
+    }
+
+    /// Sends the text an input method is composing. An empty string
+    /// ends the composition.
+    pub fn compose(&mut self, preedit: &str, cursor: Option<(usize, usize)>) {
+        self.process(&Event::ImeComposition {
+            preedit: preedit.into(),
+            cursor,
+        });
+    }
+
+    /// Sends a menu command, such as `"File:New"`.
+    pub fn command(&mut self, name: &str) {
+        self.process(&Event::Command(name.into()));
+    }
+
+    /// Returns the menu commands declared by the view tree.
+    pub fn commands(&mut self) -> Vec<CommandInfo> {
+        let mut cmds = vec![];
+        self.cx.commands(&self.view, &mut cmds);
+        cmds
+    }
+
+    /// Returns the view under a point.
+    pub fn hittest(&mut self, position: impl Into<LocalPoint>) -> Option<ViewId> {
+        let mut path = IdPath::root();
+        self.view.hittest(
+            &mut path,
+            position.into() - self.cx.root_offset,
+            &mut self.cx,
+        )
+    }
+
+    /// Returns the state of the root view, if the root is a `state` view.
+    pub fn root_state<S: 'static>(&mut self) -> StateHandle<S> {
+        StateHandle::new(IdPath::root().view_id())
+    }
+
+    /// AccessKit nodes generated by the last update.
+    pub fn access_nodes(&self) -> &[(accesskit::NodeId, accesskit::Node)] {
+        &self.access_nodes
+    }
+
+    /// Removes and returns actions which no view handled.
+    pub fn take_actions(&mut self) -> Vec<Box<dyn Any>> {
+        std::mem::take(&mut self.actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_harness_tap() {
+        let ui = state(
+            || false,
+            |pushed, _| {
+                button("button", move |cx| {
+                    cx[pushed] = true;
+                })
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<bool>();
+        assert!(!h.cx[s]);
+
+        h.tap_at([50.0, 50.0]);
+        assert!(h.cx[s]);
+    }
+
+    #[test]
+    fn test_harness_drag() {
+        let ui = state(|| 0.0, |s, _| knob(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<f32>();
+
+        h.drag([50.0, 50.0], [100.0, 50.0]);
+        assert_eq!(h.cx[s], 0.125);
+    }
+
+    #[derive(Default)]
+    struct Knobs {
+        a: f32,
+        b: f32,
+    }
+
+    make_lens!(KnobA, Knobs, f32, a);
+    make_lens!(KnobB, Knobs, f32, b);
+
+    #[test]
+    fn test_harness_multi_touch() {
+        let ui = state(Knobs::default, |s, _| {
+            hstack((knob(bind(s, KnobA {})), knob(bind(s, KnobB {}))))
+        });
+        let mut h = TestHarness::new(ui, [200.0, 100.0]);
+        let s = h.root_state::<Knobs>();
+
+        // Two fingers, one on each knob, moving at the same time.
+        h.touch_begin(7, [50.0, 50.0]);
+        h.touch_begin(9, [150.0, 50.0]);
+        h.touch_move(7, [90.0, 50.0]);
+        h.touch_move(9, [170.0, 50.0]);
+        h.touch_move(7, [100.0, 50.0]);
+        h.touch_end(9, [170.0, 50.0]);
+        h.touch_end(7, [100.0, 50.0]);
+
+        assert_eq!((h.cx[s].a, h.cx[s].b), (0.125, 0.05));
+        assert!(!h.cx.grab_cursor);
+    }
+
+    #[test]
+    fn test_harness_keys() {
+        let ui = state(String::new, |s, _| {
+            rectangle().key(move |cx, key| match key {
+                Key::Character(c) => cx[s].push(c),
+                Key::Space => cx[s].push(' '),
+                _ => (),
+            })
+        });
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<String>();
+
+        h.type_text("hi there");
+        assert_eq!(h.cx[s], "hi there");
+    }
+
+    #[test]
+    fn test_harness_command() {
+        let ui = state(
+            || 0,
+            |count, _| {
+                rectangle().command("File:New", Some(HotKey::KeyN.into()), move |cx| {
+                    cx[count] += 1
+                })
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<i32>();
+
+        let cmds = h.commands();
+        assert_eq!(cmds.len(), 1);
+        assert_eq!(cmds[0].path, "File:New");
+
+        h.command("File:New");
+        h.command("File:Open");
+        assert_eq!(h.cx[s], 1);
+    }
+
+    #[test]
+    fn test_harness_command_flags() {
+        let ui = state(
+            || (0, false),
+            |s, cx| {
+                let (count, locked) = cx[s];
+                rectangle()
+                    .command("Edit:Undo", "Ctrl+Z".parse().ok(), move |cx| cx[s].0 -= 1)
+                    .enabled(count > 0)
+                    .command("Edit:Lock", None, move |cx| cx[s].1 = !cx[s].1)
+                    .checked(locked)
+                    .command_group((command("Edit:Add").enabled(!locked).action(|| ()),))
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<(i32, bool)>();
+        let flags = |h: &TestHarness<_>| {
+            h.cx.menu_commands()
+                .iter()
+                .map(|cmd| (cmd.enabled, cmd.checked))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            flags(&h),
+            vec![(false, None), (true, Some(false)), (true, None)]
+        );
+
+        // Disabled commands don't run, even from their hotkey.
+        h.command("Edit:Undo");
+        h.cx.key_mods.control = true;
+        h.type_text("z");
+        assert_eq!(h.cx[s].0, 0);
+
+        h.cx[s].0 = 1;
+        h.command("Edit:Lock");
+        assert_eq!(
+            flags(&h),
+            vec![(true, None), (true, Some(true)), (false, None)]
+        );
+        h.type_text("z");
+        assert_eq!(h.cx[s].0, 0);
+    }
+
+    #[test]
+    fn test_harness_disabled_hotkeys() {
+        let ui = state(
+            || "ab".to_string(),
+            |s, _| {
+                text_editor(s)
+                    .command("Edit:Delete", "Backspace".parse().ok(), |_| ())
+                    .enabled(false)
+                    .command("Edit:Clear", "F2".parse().ok(), move |cx| cx[s].clear())
+                    .enabled(false)
+                    .command("Edit:Mark", "F2".parse().ok(), move |cx| cx[s].push('!'))
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<String>();
+
+        // A disabled command's key goes to the focused editor.
+        h.tap_at([50.0, 50.0]);
+        h.send_key(Key::End);
+        h.send_key(Key::Backspace);
+        assert_eq!(h.cx[s], "a");
+
+        // An enabled command further down with the same shortcut runs.
+        h.send_key(Key::F2);
+        assert_eq!(h.cx[s], "a!");
+    }
+
+    #[test]
+    fn test_harness_hotkeys() {
+        let ui = state(
+            || (0, true),
+            |s, cx| {
+                let new = rectangle().command("File:New", Some(HotKey::KeyN.into()), move |cx| {
+                    cx[s].0 += 1
+                });
+                cond(
+                    cx[s].1,
+                    new.command("File:Close", None, |_| ()),
+                    rectangle(),
+                )
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<(i32, bool)>();
+        assert_eq!(h.cx.menu_commands().len(), 2);
+
+        // Needs the shortcut modifier.
+        h.type_text("n");
+        assert_eq!(h.cx[s].0, 0);
+        h.cx.key_mods = KeyboardModifiers::primary();
+        h.type_text("n");
+        assert_eq!(h.cx[s].0, 1);
+
+        h.cx.send_command("File:New");
+        h.update();
+        assert_eq!(h.cx[s].0, 2);
+
+        // Commands are collected again when the tree changes.
+        h.cx.menu_commands_changed = false;
+        h.cx[s].1 = false;
+        h.update();
+        assert!(h.cx.menu_commands_changed);
+        assert_eq!(h.cx.menu_commands(), &[]);
+        h.type_text("n");
+        assert_eq!(h.cx[s].0, 2);
+    }
+
+    #[test]
+    fn test_harness_draw_dirty() {
+        let ui = hstack((
+            state(
+                || false,
+                |on, cx| {
+                    rectangle()
+                        .color(if cx[on] {
+                            AZURE_HIGHLIGHT
+                        } else {
+                            CONTROL_BACKGROUND
+                        })
+                        .tap(move |cx| cx[on] = !cx[on])
+                },
+            ),
+            rectangle(),
+        ));
+        let mut h = TestHarness::new(ui, [200.0, 100.0]);
+        let fills = |list: &DisplayList| {
+            list.commands
+                .iter()
+                .filter(|cmd| matches!(cmd, DrawCommand::FillRect { .. }))
+                .count()
+        };
+        assert_eq!(fills(&h.draw()), 2);
+
+        // Nothing changed, so nothing is drawn.
+        assert!(h.draw_dirty().commands.is_empty());
+
+        // Only the tapped rectangle is cleared and drawn again.
+        h.tap_at([50.0, 50.0]);
+        let list = h.draw_dirty();
+        assert_eq!(fills(&list), 2);
+        assert!(list.commands.contains(&DrawCommand::Scissor(LocalRect::new(
+            LocalPoint::zero(),
+            [100.0, 100.0].into()
+        ))));
+    }
+
+    /// Counts how many times "hello" is measured.
+    struct CountingText {
+        count: Rc<Cell<usize>>,
+        text: MonospaceText,
+    }
+
+    impl TextMeasurer for CountingText {
+        fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+            if text == "hello" {
+                self.count.set(self.count.get() + 1);
+            }
+            self.text.text_bounds(text, size, max_width)
+        }
+
+        fn glyph_positions(
+            &mut self,
+            text: &str,
+            size: u32,
+            max_width: Option<f32>,
+        ) -> Vec<LocalRect> {
+            self.text.glyph_positions(text, size, max_width)
+        }
+
+        fn line_metrics(
+            &mut self,
+            text: &str,
+            size: u32,
+            max_width: Option<f32>,
+        ) -> Vec<LineMetrics> {
+            self.text.line_metrics(text, size, max_width)
+        }
+    }
+
+    #[test]
+    fn test_harness_layout_cache() {
+        let ui = hstack((
+            text("hello").padding(Auto),
+            state(|| 0, |count, _| button("+", move |cx| cx[count] += 1)),
+        ));
+        let count = Rc::new(Cell::new(0));
+        let mut h = TestHarness::new(ui, [200.0, 100.0]).text_measurer(CountingText {
+            count: count.clone(),
+            text: MonospaceText::default(),
+        });
+
+        // Changing the button's state doesn't lay out the text again.
+        count.set(0);
+        h.tap_at([150.0, 50.0]);
+        assert_eq!(count.get(), 0);
+
+        h.resize([300.0, 100.0]);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn test_harness_layout_env() {
+        #[derive(Clone, Copy, Default)]
+        struct Width(f32);
+
+        // The width comes from outside of state, through the environment.
+        let width = Rc::new(Cell::new(10.0));
+        let w = width.clone();
+        let ui = hstack((
+            state(
+                || (),
+                move |_, _| {
+                    vstack((env(|w: Width, _| rectangle().size([w.0, 20.0])),)).env(Width(w.get()))
+                },
+            ),
+            state(
+                || 0,
+                |count, _| rectangle().size([20.0, 20.0]).tap(move |cx| cx[count] += 1),
+            ),
+        ));
+        let mut h = TestHarness::new(ui, [200.0, 100.0]);
+        assert!(h.hittest([70.0, 50.0]).is_none());
+
+        // Changing the other state lays out the rectangle at its new width.
+        width.set(60.0);
+        h.tap_at([105.0, 50.0]);
+        assert!(h.hittest([70.0, 50.0]).is_some());
+
+        // So does invalidating layout without any state changing.
+        width.set(10.0);
+        h.cx.invalidate_layout();
+        assert!(h.update());
+        assert!(h.hittest([70.0, 50.0]).is_none());
+    }
+
+    #[test]
+    fn test_harness_single_layout() {
+        let ui = state(
+            || 0,
+            |count, _| vstack((text("hello"), rectangle().tap(move |cx| cx[count] += 1))),
+        );
+        let count = Rc::new(Cell::new(0));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]).text_measurer(CountingText {
+            count: count.clone(),
+            text: MonospaceText::default(),
+        });
+
+        // Each update lays out once.
+        count.set(0);
+        h.tap_at([50.0, 20.0]);
+        assert_eq!(count.get(), 1);
+        assert!(!h.update());
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn test_harness_animation() {
+        // Moves from 0 to 1 over a second.
+        let ui = state(
+            || 0.0,
+            |x, _| {
+                rectangle().anim(move |cx, dt| {
+                    if cx[x] < 1.0 {
+                        cx[x] = f32::min(cx[x] + dt, 1.0);
+                        cx.request_animation_frame();
+                    }
+                })
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let x = h.root_state::<f32>();
+
+        // The first frame counts as one frame.
+        let first = FRAME_INTERVAL.as_secs_f32();
+        assert_eq!(h.cx[x], first);
+        assert!(h.cx.animating());
+
+        h.advance(Duration::from_millis(250));
+        assert!((h.cx[x] - (first + 0.25)).abs() < 1e-6);
+
+        h.advance(Duration::from_secs(1));
+        assert_eq!(h.cx[x], 1.0);
+
+        // One more frame to find out it's done.
+        h.advance(Duration::from_millis(16));
+        assert!(!h.cx.animating());
+
+        // After sitting idle, the first frame counts as one frame again,
+        // rather than the whole time spent idle.
+        h.advance(Duration::from_secs(5));
+        h.cx[x] = 0.0;
+        h.update();
+        assert_eq!(h.cx[x], first);
+        h.advance(Duration::from_millis(500));
+        assert!((h.cx[x] - (first + 0.5)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_harness_centers_root() {
+        let ui = rectangle().size([20.0, 20.0]);
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+
+        assert!(h.hittest([50.0, 50.0]).is_some());
+        assert!(h.hittest([10.0, 10.0]).is_none());
+    }
+}
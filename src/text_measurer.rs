@@ -0,0 +1,241 @@
+use crate::*;
+
+/// Measures text for layout, so layout can be computed without a GPU.
+///
+/// All results are in the coordinate space vger draws text in: text starts
+/// at the origin and lines go down (negative y).
+pub trait TextMeasurer {
+    /// Bounding rectangle of the text.
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect;
+
+    /// Rectangle for each glyph in the text.
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect>;
+
+    /// Glyph ranges and bounds of each line in the text.
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics>;
+}
+
+impl TextMeasurer for Vger {
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        Vger::text_bounds(self, text, size, max_width)
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        Vger::glyph_positions(self, text, size, max_width)
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        Vger::line_metrics(self, text, size, max_width)
+    }
+}
+
+/// Measures text with a function which only knows its bounds. Glyphs
+/// are spread evenly across the bounds, on a single line.
+///
+/// ```
+/// # use rui::*;
+/// let mut measure = TextBoundsFn(|text, size, _| {
+///     LocalRect::new(LocalPoint::zero(), [text.len() as f32 * size as f32, 10.0].into())
+/// });
+/// assert_eq!(measure.glyph_positions("ab", 10, None).len(), 2);
+/// ```
+pub struct TextBoundsFn<F>(pub F)
+where
+    F: FnMut(&str, u32, Option<f32>) -> LocalRect;
+
+impl<F> TextMeasurer for TextBoundsFn<F>
+where
+    F: FnMut(&str, u32, Option<f32>) -> LocalRect,
+{
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        (self.0)(text, size, max_width)
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        let bounds = (self.0)(text, size, max_width);
+        let n = text.chars().count();
+        let w = bounds.width() / n.max(1) as f32;
+        (0..n)
+            .map(|i| {
+                LocalRect::new(
+                    [bounds.min_x() + w * i as f32, bounds.min_y()].into(),
+                    [w, bounds.height()].into(),
+                )
+            })
+            .collect()
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        vec![LineMetrics {
+            glyph_start: 0,
+            glyph_end: text.chars().count(),
+            bounds: (self.0)(text, size, max_width),
+        }]
+    }
+}
+
+/// Deterministic text measurement for tests. Every glyph has the same
+/// width and lines wrap at any glyph which would exceed the maximum width.
+#[derive(Clone, Copy, Debug)]
+pub struct MonospaceText {
+    /// Width of each glyph, as a fraction of the font size.
+    pub advance: f32,
+
+    /// Height of each line, as a fraction of the font size.
+    pub line_height: f32,
+}
+
+impl Default for MonospaceText {
+    fn default() -> Self {
+        Self {
+            advance: 0.6,
+            line_height: 1.0,
+        }
+    }
+}
+
+impl MonospaceText {
+    /// Lays out the text, calling `f` with each glyph, its line and
+    /// its column.
+    fn layout(
+        &self,
+        text: &str,
+        size: u32,
+        max_width: Option<f32>,
+        mut f: impl FnMut(char, usize, usize),
+    ) -> usize {
+        let advance = self.advance * size as f32;
+        let max_columns = match max_width {
+            Some(w) => ((w / advance).floor() as usize).max(1),
+            None => usize::MAX,
+        };
+
+        let mut line = 0;
+        let mut column = 0;
+        for c in text.chars() {
+            // A newline ends its line even when the line is full.
+            if c == '\n' {
+                f(c, line, column);
+                line += 1;
+                column = 0;
+                continue;
+            }
+            if column == max_columns {
+                line += 1;
+                column = 0;
+            }
+            f(c, line, column);
+            column += 1;
+        }
+        line + 1
+    }
+}
+
+impl TextMeasurer for MonospaceText {
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        let line_height = self.line_height * size as f32;
+        let lines = self.line_metrics(text, size, max_width);
+        let width = lines.iter().map(|l| l.bounds.width()).fold(0.0, f32::max);
+        LocalRect::new(
+            [0.0, -line_height * lines.len() as f32].into(),
+            [width, line_height * lines.len() as f32].into(),
+        )
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        let advance = self.advance * size as f32;
+        let line_height = self.line_height * size as f32;
+        let mut rects = vec![];
+        self.layout(text, size, max_width, |_, line, column| {
+            rects.push(LocalRect::new(
+                [advance * column as f32, -line_height * (line + 1) as f32].into(),
+                [advance, line_height].into(),
+            ))
+        });
+        rects
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        let advance = self.advance * size as f32;
+        let line_height = self.line_height * size as f32;
+        let mut lines: Vec<LineMetrics> = vec![];
+        let mut glyph = 0;
+        let n = self.layout(text, size, max_width, |c, line, column| {
+            if line == lines.len() {
+                lines.push(LineMetrics {
+                    glyph_start: glyph,
+                    glyph_end: glyph,
+                    bounds: LocalRect::new(
+                        [0.0, -line_height * (line + 1) as f32].into(),
+                        [0.0, line_height].into(),
+                    ),
+                });
+            }
+            let metrics = &mut lines[line];
+            metrics.glyph_end = glyph + 1;
+            if c != '\n' {
+                metrics.bounds.size.width = advance * (column + 1) as f32;
+            }
+            glyph += 1;
+        });
+
+        // Include empty lines, such as after a trailing newline.
+        while lines.len() < n {
+            let line = lines.len();
+            lines.push(LineMetrics {
+                glyph_start: glyph,
+                glyph_end: glyph,
+                bounds: LocalRect::new(
+                    [0.0, -line_height * (line + 1) as f32].into(),
+                    [0.0, line_height].into(),
+                ),
+            });
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_monospace_bounds() {
+        let mut m = MonospaceText::default();
+        let bounds = m.text_bounds("hello", 10, None);
+        assert_eq!(bounds.size, [30.0, 10.0].into());
+
+        let bounds = m.text_bounds("hello world", 10, Some(36.0));
+        assert_eq!(bounds.size, [36.0, 20.0].into());
+        assert_eq!(bounds.origin, [0.0, -20.0].into());
+    }
+
+    #[test]
+    fn test_monospace_lines() {
+        let mut m = MonospaceText::default();
+        let lines = m.line_metrics("ab\ncd", 10, None);
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].glyph_start, lines[0].glyph_end), (0, 3));
+        assert_eq!((lines[1].glyph_start, lines[1].glyph_end), (3, 5));
+
+        let rects = m.glyph_positions("ab\ncd", 10, None);
+        assert_eq!(rects.len(), 5);
+        assert_eq!(rects[4].origin, [6.0, -20.0].into());
+    }
+
+    #[test]
+    fn test_monospace_newline_at_wrap() {
+        let mut m = MonospaceText::default();
+
+        // The newline fills the line exactly, so it doesn't wrap again.
+        let lines = m.line_metrics("abc\nd", 10, Some(18.0));
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].glyph_start, lines[0].glyph_end), (0, 4));
+        assert_eq!((lines[1].glyph_start, lines[1].glyph_end), (4, 5));
+        assert_eq!(
+            m.text_bounds("abc\nd", 10, Some(18.0)).size,
+            [18.0, 20.0].into()
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use crate::*;
+use std::time::Duration;
+
+/// Refers to a timer started with `Context::after` or `Context::every`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimerHandle {
+    pub(crate) id: u64,
+}
+
+impl TimerHandle {
+    /// Stops the timer. Does nothing if it already fired or was cancelled.
+    pub fn cancel(self, cx: &mut Context) {
+        cx.timers.remove(&self.id);
+    }
+
+    /// Is the timer still going to fire?
+    pub fn is_active(self, cx: &Context) -> bool {
+        cx.timers.contains_key(&self.id)
+    }
+}
+
+pub(crate) enum TimerFunc {
+    Once(Box<dyn FnOnce(&mut Context)>),
+
+    /// Fires at an interval.
+    Repeat(Duration, Box<dyn FnMut(&mut Context)>),
+}
+
+pub(crate) struct Timer {
+    /// Clock time at which the timer fires next.
+    pub deadline: Duration,
+
+    /// Taken while the function runs.
+    pub func: Option<TimerFunc>,
+
+    /// View which started the timer with the `timer` modifier. The timer
+    /// is cancelled when the view goes away.
+    pub owner: Option<ViewId>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_after_and_every() {
+        let mut h = TestHarness::new(state(|| 0, |_, _| EmptyView {}), [100.0, 100.0]);
+        let count = h.root_state::<i32>();
+        assert_eq!(h.cx.next_timer(), None);
+
+        let once =
+            h.cx.after(Duration::from_millis(500), move |cx| cx[count] += 100);
+        let every =
+            h.cx.every(Duration::from_millis(200), move |cx| cx[count] += 1);
+        assert_eq!(h.cx.next_timer(), Some(Duration::from_millis(200)));
+
+        h.advance(Duration::from_millis(100));
+        assert_eq!(h.cx[count], 0);
+
+        h.advance(Duration::from_millis(100));
+        assert_eq!(h.cx[count], 1);
+        assert_eq!(h.cx.next_timer(), Some(Duration::from_millis(200)));
+
+        h.advance(Duration::from_millis(300));
+        assert_eq!(h.cx[count], 102);
+        assert!(!once.is_active(&h.cx));
+
+        // Late updates don't fire a timer more than once.
+        h.advance(Duration::from_secs(1));
+        assert_eq!(h.cx[count], 103);
+
+        every.cancel(&mut h.cx);
+        h.advance(Duration::from_secs(1));
+        assert_eq!(h.cx[count], 103);
+        assert_eq!(h.cx.next_timer(), None);
+    }
+
+    #[test]
+    fn test_timer_modifier() {
+        let ticks = Rc::new(Cell::new(0));
+        let ticks2 = ticks.clone();
+        let ui = state(
+            || true,
+            move |show, cx| {
+                let ticks = ticks2.clone();
+                cond(
+                    cx[show],
+                    EmptyView {}.timer(Duration::from_secs(1), move |_| ticks.set(ticks.get() + 1)),
+                    EmptyView {},
+                )
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let show = h.root_state::<bool>();
+
+        h.advance(Duration::from_secs(1));
+        h.advance(Duration::from_secs(1));
+        assert_eq!(ticks.get(), 2);
+
+        // Removing the view stops its timer.
+        h.cx[show] = false;
+        h.update();
+        h.advance(Duration::from_secs(5));
+        assert_eq!(ticks.get(), 2);
+        assert_eq!(h.cx.next_timer(), None);
+    }
+}
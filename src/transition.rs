@@ -0,0 +1,389 @@
+use crate::*;
+
+/// Side of a view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Leading,
+    Trailing,
+    Top,
+    Bottom,
+}
+
+/// What a view does while it appears or disappears.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionEffect {
+    /// Fades in and out.
+    Opacity,
+
+    /// Slides in from an edge and back out to it.
+    Slide(Edge),
+
+    /// Grows from its center and shrinks back to it.
+    Scale,
+}
+
+/// How views come and go when `cond` or `list` content changes.
+/// Removed views keep drawing until they've finished going.
+///
+/// ```
+/// # use rui::*;
+/// state(
+///     || false,
+///     |on, cx| {
+///         vstack((
+///             toggle(on),
+///             cond(cx[on], circle(), rectangle())
+///                 .transition(Transition::slide(Edge::Leading).curve(Curve::spring(0.4))),
+///         ))
+///     },
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    pub effect: TransitionEffect,
+    pub curve: Curve,
+}
+
+impl Transition {
+    fn new(effect: TransitionEffect) -> Self {
+        Self {
+            effect,
+            curve: Curve::ease_in_out(0.25),
+        }
+    }
+
+    pub fn opacity() -> Self {
+        Self::new(TransitionEffect::Opacity)
+    }
+
+    pub fn slide(edge: Edge) -> Self {
+        Self::new(TransitionEffect::Slide(edge))
+    }
+
+    pub fn scale() -> Self {
+        Self::new(TransitionEffect::Scale)
+    }
+
+    /// Sets the timing of the transition. The default is
+    /// `Curve::ease_in_out(0.25)`.
+    pub fn curve(self, curve: Curve) -> Self {
+        Self { curve, ..self }
+    }
+
+    /// How much a view is showing `elapsed` seconds after it started
+    /// appearing, from 0 (hidden) to 1.
+    pub(crate) fn progress(&self, elapsed: f32) -> f32 {
+        self.curve.progress(elapsed)
+    }
+
+    /// Has a view which started appearing or disappearing `elapsed`
+    /// seconds ago finished?
+    pub(crate) fn done(&self, elapsed: f32) -> bool {
+        elapsed >= self.curve.duration()
+    }
+
+    /// Where a view of the given size can draw while it comes and goes.
+    pub(crate) fn bounds(&self, size: LocalSize) -> LocalRect {
+        let rect = LocalRect::new(LocalPoint::origin(), size);
+        match self.effect {
+            TransitionEffect::Slide(edge) => rect.union(&rect.translate(hidden(edge, size))),
+            TransitionEffect::Opacity | TransitionEffect::Scale => rect,
+        }
+    }
+
+    /// Draws a view of the given size which is `amount` shown.
+    pub(crate) fn draw(
+        &self,
+        amount: f32,
+        size: LocalSize,
+        args: &mut DrawArgs,
+        f: impl FnOnce(&mut DrawArgs),
+    ) {
+        if amount >= 1.0 {
+            f(args);
+            return;
+        }
+
+        match self.effect {
+            TransitionEffect::Opacity => {
+                let mut fade = Fade {
+                    inner: &mut *args.renderer,
+                    alpha: amount.max(0.0),
+                };
+                f(&mut DrawArgs {
+                    cx: &mut *args.cx,
+                    renderer: &mut fade,
+                });
+            }
+            TransitionEffect::Slide(edge) => {
+                args.renderer.save();
+                args.renderer.translate(hidden(edge, size) * (1.0 - amount));
+                f(args);
+                args.renderer.restore();
+            }
+            TransitionEffect::Scale => {
+                let center = LocalOffset::new(size.width, size.height) / 2.0;
+                let scale = amount.max(0.001);
+                args.renderer.save();
+                args.renderer.translate(center);
+                args.renderer.scale([scale, scale].into());
+                args.renderer.translate(-center);
+                f(args);
+                args.renderer.restore();
+            }
+        }
+    }
+}
+
+/// Where a view sliding to `edge` is when it's hidden: just past the edge.
+fn hidden(edge: Edge, size: LocalSize) -> LocalOffset {
+    match edge {
+        Edge::Leading => LocalOffset::new(-size.width, 0.0),
+        Edge::Trailing => LocalOffset::new(size.width, 0.0),
+        Edge::Top => LocalOffset::new(0.0, size.height),
+        Edge::Bottom => LocalOffset::new(0.0, -size.height),
+    }
+}
+
+/// Draws with reduced opacity.
+struct Fade<'a> {
+    inner: &'a mut dyn Renderer,
+    alpha: f32,
+}
+
+impl<'a> Fade<'a> {
+    fn fade(&self, color: Color) -> Color {
+        color.alpha(color.a * self.alpha)
+    }
+}
+
+impl<'a> TextMeasurer for Fade<'a> {
+    fn text_bounds(&mut self, text: &str, size: u32, max_width: Option<f32>) -> LocalRect {
+        self.inner.text_bounds(text, size, max_width)
+    }
+
+    fn glyph_positions(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LocalRect> {
+        self.inner.glyph_positions(text, size, max_width)
+    }
+
+    fn line_metrics(&mut self, text: &str, size: u32, max_width: Option<f32>) -> Vec<LineMetrics> {
+        self.inner.line_metrics(text, size, max_width)
+    }
+}
+
+impl<'a> Renderer for Fade<'a> {
+    fn save(&mut self) {
+        self.inner.save()
+    }
+
+    fn restore(&mut self) {
+        self.inner.restore()
+    }
+
+    fn translate(&mut self, offset: LocalOffset) {
+        self.inner.translate(offset)
+    }
+
+    fn scale(&mut self, scale: LocalOffset) {
+        self.inner.scale(scale)
+    }
+
+    fn current_transform(&self) -> LocalToWorld {
+        self.inner.current_transform()
+    }
+
+    fn scissor(&mut self, rect: LocalRect) {
+        self.inner.scissor(rect)
+    }
+
+    fn color_paint(&mut self, color: Color) -> PaintIndex {
+        let color = self.fade(color);
+        self.inner.color_paint(color)
+    }
+
+    fn linear_gradient(
+        &mut self,
+        start: LocalPoint,
+        end: LocalPoint,
+        inner_color: Color,
+        outer_color: Color,
+        glow: f32,
+    ) -> PaintIndex {
+        let inner_color = self.fade(inner_color);
+        let outer_color = self.fade(outer_color);
+        self.inner
+            .linear_gradient(start, end, inner_color, outer_color, glow)
+    }
+
+    fn fill_rect(&mut self, rect: LocalRect, radius: f32, paint: PaintIndex) {
+        self.inner.fill_rect(rect, radius, paint)
+    }
+
+    fn fill_circle(&mut self, center: LocalPoint, radius: f32, paint: PaintIndex) {
+        self.inner.fill_circle(center, radius, paint)
+    }
+
+    fn stroke_rect(
+        &mut self,
+        min: LocalPoint,
+        max: LocalPoint,
+        radius: f32,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        self.inner.stroke_rect(min, max, radius, width, paint)
+    }
+
+    fn stroke_arc(
+        &mut self,
+        center: LocalPoint,
+        radius: f32,
+        width: f32,
+        rotation: f32,
+        aperture: f32,
+        paint: PaintIndex,
+    ) {
+        self.inner
+            .stroke_arc(center, radius, width, rotation, aperture, paint)
+    }
+
+    fn stroke_segment(&mut self, a: LocalPoint, b: LocalPoint, width: f32, paint: PaintIndex) {
+        self.inner.stroke_segment(a, b, width, paint)
+    }
+
+    fn stroke_bezier(
+        &mut self,
+        a: LocalPoint,
+        b: LocalPoint,
+        c: LocalPoint,
+        width: f32,
+        paint: PaintIndex,
+    ) {
+        self.inner.stroke_bezier(a, b, c, width, paint)
+    }
+
+    fn text(&mut self, text: &str, size: u32, color: Color, max_width: Option<f32>) {
+        let color = self.fade(color);
+        self.inner.text(text, size, color, max_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::time::Duration;
+
+    /// Opacity of each filled shape.
+    fn alphas(list: &DisplayList) -> Vec<f32> {
+        list.commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                DrawCommand::FillRect {
+                    paint: RecordedPaint::Color(color),
+                    ..
+                }
+                | DrawCommand::FillCircle {
+                    paint: RecordedPaint::Color(color),
+                    ..
+                } => Some(color.a),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cond_transition() {
+        let ui = state(
+            || false,
+            |on, cx| {
+                cond(cx[on], rectangle(), circle())
+                    .transition(Transition::opacity().curve(Curve::linear(1.0)))
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let on = h.root_state::<bool>();
+        assert_eq!(alphas(&h.draw()), vec![1.0]);
+
+        h.cx[on] = true;
+        h.update();
+        h.advance(Duration::from_millis(250));
+        assert_eq!(alphas(&h.draw()), vec![0.75, 0.25]);
+
+        h.advance(Duration::from_secs(1));
+        assert_eq!(alphas(&h.draw()), vec![1.0]);
+        assert!(!h.cx.animating());
+    }
+
+    #[test]
+    fn test_list_transition() {
+        let ui = state(
+            || vec![1, 2, 3],
+            |ids, cx| {
+                list(cx[ids].clone(), |_| rectangle().size([10.0, 10.0]))
+                    .transition(Transition::opacity().curve(Curve::linear(1.0)))
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let ids = h.root_state::<Vec<i32>>();
+        assert_eq!(alphas(&h.draw()), vec![1.0, 1.0, 1.0]);
+        let bottom = h.hittest([50.0, 40.0]);
+        assert!(bottom.is_some());
+
+        // The removed row fades out where it was, behind the others.
+        h.cx[ids] = vec![2, 3];
+        h.update();
+        h.advance(Duration::from_millis(500));
+        assert_eq!(alphas(&h.draw()), vec![0.5, 1.0, 1.0]);
+
+        // The bottom row is half way to its new position.
+        assert_eq!(h.hittest([50.0, 40.0]), bottom);
+        assert_eq!(h.hittest([50.0, 44.0]), bottom);
+
+        h.advance(Duration::from_secs(1));
+        assert_eq!(alphas(&h.draw()), vec![1.0, 1.0]);
+        assert_eq!(h.hittest([50.0, 49.0]), bottom);
+
+        // An inserted row fades in.
+        h.cx[ids] = vec![2, 3, 4];
+        h.update();
+        h.advance(Duration::from_millis(250));
+        assert_eq!(alphas(&h.draw()), vec![1.0, 1.0, 0.25]);
+    }
+
+    #[test]
+    fn test_transition_repaints_rows() {
+        let ui = state(
+            || vec![1, 2],
+            |ids, cx| {
+                hstack((
+                    list(cx[ids].clone(), |_| rectangle().size([10.0, 10.0]))
+                        .transition(Transition::opacity().curve(Curve::linear(1.0))),
+                    circle().size([10.0, 10.0]),
+                ))
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let ids = h.root_state::<Vec<i32>>();
+        h.draw();
+
+        h.cx[ids] = vec![2];
+        h.update();
+        h.draw_dirty();
+
+        // Later frames repaint the removed row, but not the circle beside it.
+        assert!(h.advance(Duration::from_millis(250)));
+        assert!(!h.cx.repaint_all);
+        let list = h.draw_dirty();
+        assert!(alphas(&list).contains(&0.75));
+        assert!(!list
+            .commands
+            .iter()
+            .any(|cmd| matches!(cmd, DrawCommand::FillCircle { .. })));
+
+        // Nothing is repainted once it has gone.
+        h.advance(Duration::from_secs(1));
+        h.draw_dirty();
+        assert!(!h.advance(Duration::from_millis(16)));
+    }
+}
@@ -3,13 +3,30 @@ use std::any::{Any, TypeId};
 
 pub struct DrawArgs<'a> {
     pub cx: &'a mut Context,
-    pub vger: &'a mut Vger,
+    pub renderer: &'a mut dyn Renderer,
 }
 
 pub struct LayoutArgs<'a> {
     pub sz: LocalSize,
     pub cx: &'a mut Context,
-    pub text_bounds: &'a mut dyn FnMut(&str, u32, Option<f32>) -> LocalRect,
+    /// Measures text. Wrap a function which only knows the bounds of
+    /// text in `TextBoundsFn`.
+    pub text_bounds: &'a mut dyn TextMeasurer,
+}
+
+impl<'a> DrawArgs<'a> {
+    /// Returns false if a rectangle is outside the part of the window
+    /// being repainted, so drawing it can be skipped.
+    pub fn is_visible(&self, rect: LocalRect) -> bool {
+        match self.cx.repaint_rect {
+            Some(repaint_rect) => self
+                .renderer
+                .current_transform()
+                .outer_transformed_rect(&rect)
+                .intersects(&repaint_rect),
+            None => true,
+        }
+    }
 }
 
 impl<'a> LayoutArgs<'a> {
@@ -40,7 +57,7 @@ pub trait View: private::Sealed + 'static {
     /// Determines dirty regions which need repainting.
     fn dirty(&self, _path: &mut IdPath, _xform: LocalToWorld, _cx: &mut Context) {}
 
-    /// Draws the view using vger.
+    /// Draws the view using a `Renderer`.
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs);
 
     /// Gets IDs for views currently in use.
@@ -61,11 +78,7 @@ pub trait View: private::Sealed + 'static {
     /// Lays out subviews and return the size of the view.
     ///
     /// `sz` is the available size for the view
-    /// `vger` can be used to get text sizing
-    ///
-    /// Note that we should probably have a separate text
-    /// sizing interface so we don't need a GPU and graphics
-    /// context set up to test layout.
+    /// `text` can be used to get text sizing
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize;
 
     /// Processes an event.
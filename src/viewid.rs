@@ -21,10 +21,89 @@ impl ViewId {
     }
 }
 
-pub type IdPath = Vec<u64>;
+/// Path from the root of the view tree to a view.
+///
+/// Along with the child indices, the path keeps a rolling hash for each
+/// prefix, so finding a view's `ViewId` doesn't hash the whole path.
+#[derive(Clone, Debug, Default)]
+pub struct IdPath {
+    /// Index of each view within its parent.
+    ids: Vec<u64>,
+
+    /// `ViewId` of each prefix of the path.
+    hashes: Vec<u64>,
+}
+
+impl IdPath {
+    /// The path of the root view.
+    pub fn root() -> Self {
+        let mut path = Self::default();
+        path.push(0);
+        path
+    }
+
+    /// Descends to a child.
+    pub fn push(&mut self, id: u64) {
+        let parent = self.hashes.last().copied().unwrap_or_default();
+        self.hashes.push(hh(&(parent, id)));
+        self.ids.push(id);
+    }
+
+    /// Returns to the parent.
+    pub fn pop(&mut self) -> Option<u64> {
+        self.hashes.pop();
+        self.ids.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// The view at the end of the path.
+    pub fn view_id(&self) -> ViewId {
+        ViewId {
+            id: self.hashes.last().copied().unwrap_or_default(),
+        }
+    }
+
+    /// IDs of the views along the path, starting at the root.
+    pub fn view_ids(&self) -> impl Iterator<Item = ViewId> + '_ {
+        self.hashes.iter().map(|id| ViewId { id: *id })
+    }
+}
 
 pub fn hh<H: Hash>(index: &H) -> u64 {
     let mut hasher = DefaultHasher::new();
     index.hash(&mut hasher);
     hasher.finish()
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_id_path() {
+        let mut path = IdPath::root();
+        let root = path.view_id();
+        path.push(1);
+        let child = path.view_id();
+        assert_ne!(root, child);
+        assert_eq!(path.view_ids().collect::<Vec<_>>(), vec![root, child]);
+
+        // The same path always has the same id.
+        path.pop();
+        assert_eq!(path.view_id(), root);
+        path.push(1);
+        assert_eq!(path.view_id(), child);
+
+        path.pop();
+        path.push(2);
+        assert_ne!(path.view_id(), child);
+    }
+}
@@ -29,7 +29,8 @@ where
         actions: &mut Vec<Box<dyn Any>>,
     ) {
         if let Event::Anim = event {
-            (self.func)(cx, 1.0 / 60.0) // XXX: assume 60fps for now.
+            let dt = cx.frame_delta();
+            (self.func)(cx, dt)
         }
 
         path.push(0);
@@ -0,0 +1,219 @@
+use crate::*;
+
+/// Progress of `animated` towards the binding's value.
+#[derive(Clone)]
+struct Animation<T> {
+    from: T,
+    to: T,
+    value: T,
+
+    /// How fast the value is moving, for springs.
+    velocity: T,
+
+    /// Seconds since the binding changed.
+    elapsed: f32,
+
+    /// Has the value reached `to`?
+    done: bool,
+}
+
+impl<T: Animatable> Animation<T> {
+    fn at_rest(value: T) -> Self {
+        Self {
+            from: value.clone(),
+            to: value.clone(),
+            velocity: value.combine(0.0, &value, 0.0),
+            value,
+            elapsed: 0.0,
+            done: true,
+        }
+    }
+
+    /// Heads for a new target, starting from wherever the value got to
+    /// at the speed it was going.
+    fn retarget(&self, to: T) -> Self {
+        // Lerping by nothing turns colors into gradients and back,
+        // to match the target.
+        let value = self.value.lerp(&to, 0.0);
+        let still = to.combine(0.0, &to, 0.0);
+        Self {
+            from: value.clone(),
+            velocity: self.velocity.lerp(&still, 0.0),
+            value,
+            to,
+            elapsed: 0.0,
+            done: false,
+        }
+    }
+
+    fn step(&mut self, curve: Curve, dt: f32) {
+        self.elapsed += dt;
+        match curve {
+            Curve::Spring { response } => {
+                let (value, velocity) =
+                    spring_step(&self.value, &self.velocity, &self.to, response, dt);
+                self.value = value;
+                self.velocity = velocity;
+
+                // Close enough to stop once within 0.1% of the distance
+                // travelled, bearing in mind the precision of `to`.
+                let span = self.from.combine(1.0, &self.to, -1.0).magnitude();
+                let tolerance = 0.001 * span.max(0.001 * self.to.magnitude()).max(1e-6);
+                let omega = std::f32::consts::TAU / response;
+                self.done = self.value.combine(1.0, &self.to, -1.0).magnitude() < tolerance
+                    && self.velocity.magnitude() < tolerance * omega;
+            }
+            _ => {
+                self.value = self.from.lerp(&self.to, curve.progress(self.elapsed));
+                self.done = self.elapsed >= curve.duration();
+            }
+        }
+        if self.done {
+            self.value = self.to.clone();
+            self.velocity = self.to.combine(0.0, &self.to, 0.0);
+        }
+    }
+}
+
+/// Animates changes to a value. When the value behind `binding` changes,
+/// `f` is passed values moving from the old one to the new one along
+/// `curve`, rather than jumping straight to the new value. A change in
+/// the middle of an animation starts from wherever the value got to,
+/// and springs keep their velocity.
+///
+/// Bindings in rui are handles into state with nowhere to keep an
+/// animation's progress, so rather than an animated binding, this is a
+/// view which holds the progress and builds its content with `f`.
+///
+/// ```
+/// # use rui::*;
+/// state(
+///     || 0.0,
+///     |x, _| {
+///         animated(x, Curve::spring(0.3), |x, _| circle().offset([x, 0.0]))
+///             .tap(move |cx| cx[x] = 100.0 - cx[x])
+///     },
+/// );
+/// ```
+pub fn animated<T: Animatable, V: View>(
+    binding: impl Binding<T>,
+    curve: Curve,
+    f: impl Fn(T, &Context) -> V + 'static,
+) -> impl View {
+    state(
+        || None,
+        move |s: StateHandle<Option<Animation<T>>>, cx| {
+            let value = match &cx[s] {
+                Some(animation) => animation.value.clone(),
+                None => binding.get(cx).clone(),
+            };
+            f(value, cx).anim(move |cx, dt| {
+                let target = binding.get(cx).clone();
+                let animation = match cx[s].as_ref() {
+                    // Nothing to animate from yet.
+                    None => Animation::at_rest(target),
+                    Some(animation) if animation.to != target => animation.retarget(target),
+                    Some(animation) if !animation.done => {
+                        let mut animation = animation.clone();
+                        animation.step(curve, dt);
+                        animation
+                    }
+                    // At rest.
+                    Some(_) => return,
+                };
+                if !animation.done {
+                    cx.request_animation_frame();
+                }
+                cx[s] = Some(animation);
+            })
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_animated() {
+        let shown = Rc::new(Cell::new(-1.0));
+        let shown2 = shown.clone();
+        let ui = state(
+            || 0.0,
+            move |x, _| {
+                let shown = shown2.clone();
+                animated(x, Curve::linear(1.0), move |x, _| {
+                    shown.set(x);
+                    rectangle()
+                })
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let x = h.root_state::<f32>();
+        assert_eq!(shown.get(), 0.0);
+        assert!(!h.cx.animating());
+
+        h.cx[x] = 100.0;
+        h.update();
+        assert_eq!(shown.get(), 0.0);
+        assert!(h.cx.animating());
+
+        h.advance(Duration::from_millis(250));
+        assert_eq!(shown.get(), 25.0);
+
+        // Turn around half way.
+        h.advance(Duration::from_millis(250));
+        h.cx[x] = 0.0;
+        h.update();
+        h.advance(Duration::from_millis(500));
+        assert_eq!(shown.get(), 25.0);
+
+        h.advance(Duration::from_secs(1));
+        assert_eq!(shown.get(), 0.0);
+        h.advance(Duration::from_millis(16));
+        assert!(!h.cx.animating());
+    }
+
+    #[test]
+    fn test_animated_spring() {
+        let shown = Rc::new(Cell::new(-1.0));
+        let shown2 = shown.clone();
+        let ui = state(
+            || 0.0,
+            move |x, _| {
+                let shown = shown2.clone();
+                animated(x, Curve::spring(0.5), move |x, _| {
+                    shown.set(x);
+                    rectangle()
+                })
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let x = h.root_state::<f32>();
+
+        h.cx[x] = 100.0;
+        h.update();
+        for _ in 0..6 {
+            h.advance(Duration::from_millis(16));
+        }
+        let turned_at = shown.get();
+        assert!(turned_at > 0.0 && turned_at < 100.0);
+
+        // Sent back, it carries on the way it was going before turning around.
+        h.cx[x] = 0.0;
+        h.update();
+        h.advance(Duration::from_millis(16));
+        assert!(shown.get() > turned_at);
+
+        // Then it comes to rest at the new value.
+        for _ in 0..200 {
+            h.advance(Duration::from_millis(16));
+        }
+        assert_eq!(shown.get(), 0.0);
+        assert!(!h.cx.animating());
+    }
+}
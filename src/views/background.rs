@@ -37,12 +37,18 @@ where
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        if let Some(size) = args.cx.cached_layout(path, args.sz) {
+            return size;
+        }
+
         path.push(0);
         let child_size = self.child.layout(path, args);
         path.pop();
         path.push(1);
         self.background.layout(path, &mut args.size(child_size));
         path.pop();
+
+        args.cx.cache_layout(path, args.sz, child_size);
         child_size
     }
 
@@ -72,6 +78,7 @@ where
     }
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
         path.push(0);
         self.child.gc(path, cx, map);
         path.pop();
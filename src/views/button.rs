@@ -54,13 +54,15 @@ mod tests {
         );
         let sz = [100.0, 100.0].into();
 
-        let mut path = vec![0];
+        let mut path = IdPath::root();
         let button_sz = ui.layout(
             &mut path,
             &mut LayoutArgs {
                 sz,
                 cx: &mut cx,
-                text_bounds: &mut |_, _, _| LocalRect::new(LocalPoint::zero(), [90.0, 90.0].into()),
+                text_bounds: &mut TextBoundsFn(|_, _, _| {
+                    LocalRect::new(LocalPoint::zero(), [90.0, 90.0].into())
+                }),
             },
         );
         assert!(path.len() == 1);
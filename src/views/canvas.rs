@@ -8,14 +8,14 @@ pub struct Canvas<F> {
 
 impl<F> View for Canvas<F>
 where
-    F: Fn(&mut Context, LocalRect, &mut Vger) + 'static,
+    F: Fn(&mut Context, LocalRect, &mut dyn Renderer) + 'static,
 {
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         let rect = args.cx.get_layout(path).rect;
 
-        args.vger.save();
-        (self.func)(args.cx, rect, args.vger);
-        args.vger.restore();
+        args.renderer.save();
+        (self.func)(args.cx, rect, args.renderer);
+        args.renderer.restore();
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
@@ -44,8 +44,8 @@ where
     }
 }
 
-/// Canvas for GPU drawing with Vger. See https://github.com/audulus/vger-rs.
-pub fn canvas<F: Fn(&mut Context, LocalRect, &mut Vger) + 'static>(f: F) -> impl View {
+/// Canvas for GPU drawing. On screen this draws with Vger, see https://github.com/audulus/vger-rs.
+pub fn canvas<F: Fn(&mut Context, LocalRect, &mut dyn Renderer) + 'static>(f: F) -> impl View {
     Canvas { func: f }
 }
 
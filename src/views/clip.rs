@@ -2,7 +2,7 @@ use crate::*;
 use std::any::Any;
 
 pub struct Clip<V> {
-    child: V,
+    pub(crate) child: V,
 }
 
 impl<V> Clip<V>
@@ -35,14 +35,26 @@ where
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
-        let rect = self.geom(path, args.cx);
+        let mut rect = self.geom(path, args.cx);
+        if !args.is_visible(rect) {
+            return;
+        }
+
+        // Don't draw outside the part of the window being repainted.
+        if let Some(repaint_rect) = args.cx.repaint_rect {
+            let to_local = args.renderer.current_transform().inverse();
+            if let Some(to_local) = to_local {
+                let repaint_rect = to_local.outer_transformed_rect(&repaint_rect);
+                rect = rect.intersection(&repaint_rect).unwrap_or_default();
+            }
+        }
 
-        args.vger.save();
-        args.vger.scissor(rect);
+        args.renderer.save();
+        args.renderer.scissor(rect);
         path.push(0);
         self.child.draw(path, args);
         path.pop();
-        args.vger.restore();
+        args.renderer.restore();
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
@@ -4,8 +4,10 @@ use std::any::Any;
 pub struct Command<V, F> {
     child: V,
     name: String,
-    key: Option<HotKey>,
+    key: Option<Shortcut>,
     func: F,
+    enabled: bool,
+    checked: Option<bool>,
 }
 
 impl<V, F> Command<V, F>
@@ -13,12 +15,42 @@ where
     V: View,
     F: Fn(&mut Context) + 'static,
 {
-    pub fn new(v: V, name: String, key: Option<HotKey>, f: F) -> Self {
+    pub fn new(v: V, name: String, key: Option<Shortcut>, f: F) -> Self {
         Self {
             child: v,
             name,
             key,
             func: f,
+            enabled: true,
+            checked: None,
+        }
+    }
+
+    /// Disables the command when false, so it's greyed out and
+    /// doesn't run.
+    ///
+    /// ```
+    /// # use rui::*;
+    /// state(
+    ///     || vec![1, 2, 3],
+    ///     |undo, cx| {
+    ///         rectangle()
+    ///             .command("Edit:Undo", "Primary+Z".parse().ok(), move |cx| {
+    ///                 cx[undo].pop();
+    ///             })
+    ///             .enabled(!cx[undo].is_empty())
+    ///     },
+    /// );
+    /// ```
+    pub fn enabled(self, enabled: bool) -> Self {
+        Self { enabled, ..self }
+    }
+
+    /// Makes the command a toggle, shown with a check mark when on.
+    pub fn checked(self, checked: bool) -> Self {
+        Self {
+            checked: Some(checked),
+            ..self
         }
     }
 }
@@ -36,7 +68,7 @@ where
         actions: &mut Vec<Box<dyn Any>>,
     ) {
         if let Event::Command(name) = &event {
-            if *name == self.name {
+            if *name == self.name && self.enabled {
                 (self.func)(cx);
             }
         }
@@ -72,6 +104,8 @@ where
         cmds.push(CommandInfo {
             path: self.name.clone(),
             key: self.key,
+            enabled: self.enabled,
+            checked: self.checked,
         })
     }
 
@@ -102,7 +136,13 @@ where
 pub trait CommandBase {
     fn exec(&self);
     fn name(&self) -> String;
-    fn key(&self) -> Option<HotKey>;
+    fn key(&self) -> Option<Shortcut>;
+    fn enabled(&self) -> bool {
+        true
+    }
+    fn checked(&self) -> Option<bool> {
+        None
+    }
 }
 
 pub trait CommandTuple {
@@ -271,7 +311,7 @@ where
     ) {
         if let Event::Command(name) = &event {
             self.cmds.foreach_cmd(&mut |cmd| {
-                if cmd.name() == *name {
+                if cmd.name() == *name && cmd.enabled() {
                     cmd.exec();
                 }
             });
@@ -309,6 +349,8 @@ where
             cmds.push(CommandInfo {
                 path: cmd.name(),
                 key: cmd.key(),
+                enabled: cmd.enabled(),
+                checked: cmd.checked(),
             })
         });
     }
@@ -336,7 +378,9 @@ impl<V, C> private::Sealed for CommandGroup<V, C> {}
 
 pub struct NullCommand {
     name: String,
-    key: Option<HotKey>,
+    key: Option<Shortcut>,
+    enabled: bool,
+    checked: Option<bool>,
 }
 
 /// Specifies a menu command.
@@ -344,6 +388,8 @@ pub fn command(name: &str) -> NullCommand {
     NullCommand {
         name: name.into(),
         key: None,
+        enabled: true,
+        checked: None,
     }
 }
 
@@ -352,17 +398,34 @@ impl CommandBase for NullCommand {
     fn name(&self) -> String {
         self.name.clone()
     }
-    fn key(&self) -> Option<HotKey> {
-        None
+    fn key(&self) -> Option<Shortcut> {
+        self.key
+    }
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+    fn checked(&self) -> Option<bool> {
+        self.checked
     }
 }
 
 impl NullCommand {
     /// Adds a hotkey to the menu command.
-    pub fn hotkey(self, key: HotKey) -> Self {
+    pub fn hotkey(self, key: impl Into<Shortcut>) -> Self {
         Self {
-            name: self.name,
-            key: Some(key),
+            key: Some(key.into()),
+            ..self
+        }
+    }
+    /// Disables the menu command when false.
+    pub fn enabled(self, enabled: bool) -> Self {
+        Self { enabled, ..self }
+    }
+    /// Shows a check mark on the menu command when true.
+    pub fn checked(self, checked: bool) -> Self {
+        Self {
+            checked: Some(checked),
+            ..self
         }
     }
     /// Adds an action to the menu command.
@@ -370,6 +433,8 @@ impl NullCommand {
         Command2 {
             name: self.name,
             key: self.key,
+            enabled: self.enabled,
+            checked: self.checked,
             func,
         }
     }
@@ -377,7 +442,9 @@ impl NullCommand {
 
 pub struct Command2<F: Fn()> {
     name: String,
-    key: Option<HotKey>,
+    key: Option<Shortcut>,
+    enabled: bool,
+    checked: Option<bool>,
     func: F,
 }
 
@@ -391,9 +458,15 @@ where
     fn name(&self) -> String {
         self.name.clone()
     }
-    fn key(&self) -> Option<HotKey> {
+    fn key(&self) -> Option<Shortcut> {
         self.key
     }
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+    fn checked(&self) -> Option<bool> {
+        self.checked
+    }
 }
 
 impl<F> Command2<F>
@@ -401,11 +474,21 @@ where
     F: Fn(),
 {
     /// Adds a hotkey to the menu command.
-    pub fn hotkey(self, key: HotKey) -> Self {
+    pub fn hotkey(self, key: impl Into<Shortcut>) -> Self {
         Self {
-            name: self.name,
-            key: Some(key),
-            func: self.func,
+            key: Some(key.into()),
+            ..self
+        }
+    }
+    /// Disables the menu command when false.
+    pub fn enabled(self, enabled: bool) -> Self {
+        Self { enabled, ..self }
+    }
+    /// Shows a check mark on the menu command when true.
+    pub fn checked(self, checked: bool) -> Self {
+        Self {
+            checked: Some(checked),
+            ..self
         }
     }
 }
@@ -0,0 +1,442 @@
+use crate::*;
+
+use std::any::Any;
+
+/// Most commands the palette shows at once. The rest scroll into view.
+const PALETTE_ROWS: usize = 10;
+
+const PALETTE_ROW_HEIGHT: f32 = 30.0;
+
+const PALETTE_WIDTH: f32 = 400.0;
+
+/// What's been typed into the palette.
+#[derive(Clone, Default)]
+struct PaletteState {
+    query: String,
+
+    /// Index of the highlighted command in the matches.
+    selected: usize,
+
+    /// How far the matches have been scrolled.
+    scroll: LocalOffset,
+
+    /// Where the keyboard focus was before the palette took it.
+    previous_focus: Option<ViewId>,
+}
+
+make_lens!(PaletteScroll, PaletteState, LocalOffset, scroll);
+
+impl PaletteState {
+    /// Highlights a command, scrolling it into view.
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        let top = index as f32 * PALETTE_ROW_HEIGHT;
+        let bottom = top + PALETTE_ROW_HEIGHT;
+        let height = PALETTE_ROWS as f32 * PALETTE_ROW_HEIGHT;
+        self.scroll.y = self.scroll.y.min(top).max(bottom - height);
+    }
+}
+
+/// Holds the keyboard focus while the palette is open, so typing doesn't
+/// also go to a focused `text_editor`, and gives it back once closed.
+struct PaletteFocus<V> {
+    child: V,
+    shown: bool,
+    state: StateHandle<PaletteState>,
+}
+
+impl<V: View> View for PaletteFocus<V> {
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let vid = cx.view_id(path);
+        if self.shown && cx.focused_id != Some(vid) {
+            cx[self.state].previous_focus = cx.focused_id;
+            cx.set_focus(Some(vid));
+        } else if !self.shown && cx.focused_id == Some(vid) {
+            let previous = cx[self.state].previous_focus.take();
+            cx.set_focus(previous);
+        }
+
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        path.push(0);
+        self.child.gc(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V> private::Sealed for PaletteFocus<V> {}
+
+/// Scores how well `query` matches `text`, or `None` if it doesn't.
+/// The characters of `query` must appear in `text` in order, ignoring
+/// case. Runs of consecutive characters and matches at the start of
+/// words, such as after the `:` in `"File:New"`, score higher.
+pub(crate) fn fuzzy_match(query: &str, text: &str) -> Option<i32> {
+    let mut query = query.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut score = 0;
+    let mut run = 0;
+    let mut word_start = true;
+    for c in text.chars() {
+        let wanted = match query.peek() {
+            Some(wanted) => *wanted,
+            None => break,
+        };
+        if c.to_lowercase().eq(wanted.to_lowercase()) {
+            query.next();
+            run += 1;
+            score += run;
+            if word_start {
+                score += 10;
+            }
+        } else {
+            run = 0;
+            score -= 1;
+        }
+        word_start = !c.is_alphanumeric();
+    }
+    if query.peek().is_none() {
+        Some(score)
+    } else {
+        None
+    }
+}
+
+/// Commands matching `query`, best first.
+fn palette_matches(commands: &[CommandInfo], query: &str) -> Vec<CommandInfo> {
+    let mut matches: Vec<(i32, &CommandInfo)> = vec![];
+    for cmd in commands {
+        // Commands can be declared by more than one view.
+        if matches.iter().any(|(_, m)| m.path == cmd.path) {
+            continue;
+        }
+        if let Some(score) = fuzzy_match(query, &cmd.path) {
+            matches.push((score, cmd));
+        }
+    }
+    // Stable, so equal scores stay in tree order.
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(_, cmd)| cmd.clone()).collect()
+}
+
+fn close_palette(cx: &mut Context, open: impl Binding<bool>, s: StateHandle<PaletteState>) {
+    *open.get_mut(cx) = false;
+    cx[s] = PaletteState {
+        previous_focus: cx[s].previous_focus,
+        ..Default::default()
+    };
+}
+
+/// Lists the menu commands in the view tree, narrowed down by typing.
+/// Arrow keys move the highlight, scrolling through the matches, Enter
+/// runs the highlighted command and Escape closes the palette. The
+/// palette holds the keyboard focus while it's open. Disabled commands
+/// are greyed out and toggles which are on are marked. Commands are
+/// sent as `Event::Command` on the next update.
+///
+/// Put the palette on top of the rest of the UI, so it sees key presses
+/// first, and open it with a command of its own:
+///
+/// ```
+/// # use rui::*;
+/// state(
+///     || false,
+///     |open, _| {
+///         zstack((
+///             rectangle().command("File:New", None, |_| println!("new")),
+///             command_palette(open),
+///         ))
+///         .command("View:Command Palette", "Primary+Shift+P".parse().ok(), move |cx| {
+///             cx[open] = true
+///         })
+///     },
+/// );
+/// ```
+pub fn command_palette(open: impl Binding<bool>) -> impl View {
+    state(PaletteState::default, move |s, cx| {
+        let shown = *open.get(cx);
+        let matches = palette_matches(cx.menu_commands(), &cx[s].query);
+        let selected = cx[s].selected.min(matches.len().saturating_sub(1));
+        let query = if cx[s].query.is_empty() {
+            text("Type a command").color(MEDIUM_GRAY)
+        } else {
+            text(&cx[s].query)
+        };
+        let rows = matches.len().min(PALETTE_ROWS);
+
+        let commands = matches.clone();
+        let list = lazy_list(matches.len(), PALETTE_ROW_HEIGHT, move |i| {
+            let cmd = &commands[i];
+            let path = cmd.path.clone();
+            let enabled = cmd.enabled;
+            let shortcut = cmd.key.map(|key| key.to_string()).unwrap_or_default();
+            hstack((
+                circle()
+                    .color(if cmd.checked == Some(true) {
+                        AZURE_HIGHLIGHT
+                    } else {
+                        CLEAR_COLOR
+                    })
+                    .size([8.0, 8.0]),
+                text(&cmd.path).color(if enabled { TEXT_COLOR } else { MEDIUM_GRAY }),
+                spacer(),
+                text(&shortcut).color(MEDIUM_GRAY),
+            ))
+            .padding(Auto)
+            .background(rectangle().color(if i == selected {
+                BUTTON_HOVER_COLOR
+            } else {
+                CLEAR_COLOR
+            }))
+            .tap(move |cx| {
+                if enabled {
+                    close_palette(cx, open, s);
+                    cx.send_command(&path);
+                }
+            })
+        })
+        .position(bind(s, PaletteScroll {}));
+
+        // Keys are handled on the query row, since `key` doesn't pass
+        // events on and the rows below take taps and scrolling.
+        let query = query
+            .padding(Auto)
+            .size([PALETTE_WIDTH, PALETTE_ROW_HEIGHT])
+            .key(move |cx, key| match key {
+                Key::Backspace => {
+                    cx[s].query.pop();
+                    cx[s].select(0);
+                }
+                Key::ArrowDown if selected + 1 < matches.len() => cx[s].select(selected + 1),
+                Key::ArrowUp => cx[s].select(selected.saturating_sub(1)),
+                Key::Enter => {
+                    if let Some(cmd) = matches.get(selected).filter(|cmd| cmd.enabled) {
+                        close_palette(cx, open, s);
+                        cx.send_command(&cmd.path);
+                    }
+                }
+                Key::Escape => close_palette(cx, open, s),
+                _ => (),
+            })
+            .text_input(move |cx, text| {
+                cx[s].query.push_str(text);
+                cx[s].select(0);
+            });
+
+        PaletteFocus {
+            child: cond(
+                shown,
+                vstack((
+                    query,
+                    list.size([PALETTE_WIDTH, PALETTE_ROW_HEIGHT * rows as f32]),
+                ))
+                .size([PALETTE_WIDTH, PALETTE_ROW_HEIGHT * (rows + 1) as f32])
+                .background(
+                    rectangle()
+                        .corner_radius(BUTTON_CORNER_RADIUS)
+                        .color(BUTTON_BACKGROUND_COLOR),
+                ),
+                EmptyView {},
+            ),
+            shown,
+            state: s,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("fn", "File:New").is_some());
+        assert!(fuzzy_match("nf", "File:New").is_none());
+        assert!(fuzzy_match("", "File:New").is_some());
+        assert!(fuzzy_match("file new", "File:New").is_some());
+
+        // Word starts beat letters in the middle.
+        assert!(fuzzy_match("fn", "File:New") > fuzzy_match("fn", "Edit:Find Next Line"));
+        // Runs beat scattered letters.
+        assert!(fuzzy_match("ope", "reopen") > fuzzy_match("ope", "rollup date"));
+    }
+
+    #[test]
+    fn test_command_palette() {
+        let ran = Rc::new(RefCell::new(String::new()));
+        let ran2 = ran.clone();
+        let ui = state(
+            || false,
+            move |open, _| {
+                let ran = ran2.clone();
+                let run = move |name: &'static str| {
+                    let ran = ran.clone();
+                    move |_: &mut Context| *ran.borrow_mut() = name.into()
+                };
+                zstack((
+                    rectangle()
+                        .command("File:New", "Ctrl+N".parse().ok(), run("new"))
+                        .command("File:Open", None, run("open"))
+                        .command("Edit:Find", None, run("find")),
+                    command_palette(open),
+                ))
+            },
+        );
+        let mut h = TestHarness::new(ui, [500.0, 500.0]);
+        let open = h.root_state::<bool>();
+
+        // Keys go elsewhere while closed.
+        h.type_text("fo");
+        h.send_key(Key::Enter);
+        assert_eq!(*ran.borrow(), "");
+
+        h.cx[open] = true;
+        h.update();
+        // Edit:Find matches last, and the highlight stops there.
+        h.type_text("fi");
+        h.send_key(Key::ArrowDown);
+        h.send_key(Key::ArrowDown);
+        h.send_key(Key::ArrowDown);
+        h.send_key(Key::Enter);
+        assert!(!h.cx[open]);
+        h.update();
+        assert_eq!(*ran.borrow(), "find");
+
+        // Reopening starts over.
+        h.cx[open] = true;
+        h.update();
+        h.type_text("new");
+        h.send_key(Key::Enter);
+        h.update();
+        assert_eq!(*ran.borrow(), "new");
+
+        h.cx[open] = true;
+        h.update();
+        h.type_text("open");
+        h.send_key(Key::Escape);
+        h.update();
+        assert!(!h.cx[open]);
+        assert_eq!(*ran.borrow(), "new");
+    }
+
+    #[test]
+    fn test_command_palette_scrolls() {
+        let ran = Rc::new(RefCell::new(String::new()));
+        let ran2 = ran.clone();
+        let ui = state(
+            || true,
+            move |open, _| {
+                let ran = ran2.clone();
+                zstack((
+                    list((0..15).collect(), move |i: &usize| {
+                        let ran = ran.clone();
+                        let name = format!("Cmd:{:02}", i);
+                        rectangle().command(&name.clone(), None, move |_| {
+                            *ran.borrow_mut() = name.clone()
+                        })
+                    }),
+                    command_palette(open),
+                ))
+            },
+        );
+        let mut h = TestHarness::new(ui, [500.0, 500.0]);
+        h.update();
+
+        // Commands past the first screenful can be reached.
+        for _ in 0..12 {
+            h.send_key(Key::ArrowDown);
+        }
+        h.send_key(Key::Enter);
+        h.update();
+        assert_eq!(*ran.borrow(), "Cmd:12");
+    }
+
+    #[test]
+    fn test_command_palette_focus() {
+        let ui = state(String::new, |text, _| {
+            state(
+                || false,
+                move |open, _| {
+                    zstack((text_editor(text), command_palette(open))).command(
+                        "View:Command Palette",
+                        None,
+                        move |cx| cx[open] = true,
+                    )
+                },
+            )
+        });
+        let mut h = TestHarness::new(ui, [500.0, 500.0]);
+        let text = h.root_state::<String>();
+
+        h.tap_at([250.0, 250.0]);
+        h.type_text("a");
+        assert_eq!(h.cx[text], "a");
+
+        // Typing goes to the palette while it's open.
+        h.command("View:Command Palette");
+        h.update();
+        h.type_text("xyz");
+        assert_eq!(h.cx[text], "a");
+
+        // Closing it gives the focus back.
+        h.send_key(Key::Escape);
+        h.update();
+        h.type_text("b");
+        assert_eq!(h.cx[text], "ab");
+    }
+}
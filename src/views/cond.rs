@@ -1,11 +1,140 @@
 use crate::*;
 use std::any::Any;
 
+/// Per-view state for `cond` with a transition.
+struct CondState {
+    /// Branch being shown, or `None` before the first frame.
+    shown: Option<bool>,
+
+    /// Seconds since the branch changed.
+    elapsed: f32,
+
+    /// Sizes of the `if_true` and `if_false` views from the last layout.
+    sizes: [LocalSize; 2],
+
+    /// Where the views can draw until the switch finishes, as of the
+    /// last draw. Repainted on each animation frame.
+    region: Option<(LocalRect, LocalToWorld)>,
+}
+
 /// Struct for `cond`
 pub struct Cond<V0, V1> {
     cond: bool,
     if_true: V0,
     if_false: V1,
+    transition: Option<Transition>,
+}
+
+impl<V0, V1> Cond<V0, V1>
+where
+    V0: View,
+    V1: View,
+{
+    /// Animates switching between the views. The old view keeps
+    /// drawing until it has gone.
+    pub fn transition(self, transition: Transition) -> Self {
+        Self {
+            transition: Some(transition),
+            ..self
+        }
+    }
+
+    fn state<'a>(&self, path: &IdPath, cx: &'a mut Context) -> &'a mut CondState {
+        let id = cx.init_state(path, &|| CondState {
+            shown: None,
+            elapsed: 0.0,
+            sizes: [LocalSize::zero(); 2],
+            region: None,
+        });
+
+        // Go around get_mut since animating shouldn't mark anything dirty.
+        cx.state_map
+            .get_mut(&id)
+            .unwrap()
+            .state
+            .downcast_mut::<CondState>()
+            .unwrap()
+    }
+
+    /// How much the current view is showing, if the other one
+    /// is still going.
+    fn switching(&self, path: &IdPath, cx: &mut Context) -> Option<f32> {
+        let transition = self.transition?;
+        let state = self.state(path, cx);
+        if state.shown.is_some() && !transition.done(state.elapsed) {
+            Some(transition.progress(state.elapsed))
+        } else {
+            None
+        }
+    }
+
+    fn animate(&self, transition: Transition, path: &IdPath, cx: &mut Context) {
+        let dt = cx.frame_delta();
+        let duration = transition.curve.duration();
+        let state = self.state(path, cx);
+        let was_switching = state.shown.is_some() && !transition.done(state.elapsed);
+        state.elapsed += dt;
+        match state.shown {
+            // Show the first view without a transition.
+            None => state.elapsed = duration,
+            Some(shown) if shown != self.cond => {
+                // Turn around if the last switch hasn't finished.
+                state.elapsed = (duration - state.elapsed).max(0.0);
+            }
+            _ => (),
+        }
+        state.shown = Some(self.cond);
+
+        let switching = !transition.done(state.elapsed);
+        let region = state.region;
+        if switching {
+            cx.request_animation_frame();
+        }
+        if switching || was_switching {
+            // Nothing else changes, so just repaint where the views are.
+            if let Some((rect, xform)) = region {
+                cx.repaint_animation(rect, xform);
+            }
+        }
+    }
+
+    fn draw_branch(&self, branch: bool, path: &mut IdPath, args: &mut DrawArgs) {
+        if branch {
+            path.push(0);
+            self.if_true.draw(path, args);
+            path.pop();
+        } else {
+            path.push(1);
+            self.if_false.draw(path, args);
+            path.pop();
+        }
+    }
+
+    fn layout_branch(&self, branch: bool, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        if branch {
+            path.push(0);
+            let sz = self.if_true.layout(path, args);
+            path.pop();
+            sz
+        } else {
+            path.push(1);
+            let sz = self.if_false.layout(path, args);
+            path.pop();
+            sz
+        }
+    }
+
+    fn gc_branch(&self, branch: bool, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        if branch {
+            path.push(0);
+            self.if_true.gc(path, cx, map);
+            path.pop();
+        } else {
+            path.push(1);
+            self.if_false.gc(path, cx, map);
+            path.pop();
+        }
+    }
 }
 
 impl<V0, V1> View for Cond<V0, V1>
@@ -20,6 +149,10 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
+        if let (Event::Anim, Some(transition)) = (event, self.transition) {
+            self.animate(transition, path, cx);
+        }
+
         if self.cond {
             path.push(0);
             self.if_true.process(event, path, cx, actions);
@@ -32,29 +165,49 @@ where
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
-        if self.cond {
-            path.push(0);
-            self.if_true.draw(path, args);
-            path.pop();
-        } else {
-            path.push(1);
-            self.if_false.draw(path, args);
-            path.pop();
+        match (self.transition, self.switching(path, args.cx)) {
+            (Some(transition), Some(progress)) => {
+                let xform = args.renderer.current_transform();
+                let state = self.state(path, args.cx);
+                let sizes = state.sizes;
+                let size = |branch: bool| sizes[if branch { 0 } else { 1 }];
+                let region = transition
+                    .bounds(size(true))
+                    .union(&transition.bounds(size(false)));
+                state.region = Some((region, xform));
+
+                let old = !self.cond;
+                transition.draw(1.0 - progress, size(old), args, |args| {
+                    self.draw_branch(old, path, args)
+                });
+                transition.draw(progress, size(self.cond), args, |args| {
+                    self.draw_branch(self.cond, path, args)
+                });
+            }
+            _ => self.draw_branch(self.cond, path, args),
         }
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
-        if self.cond {
-            path.push(0);
-            let sz = self.if_true.layout(path, args);
-            path.pop();
-            sz
-        } else {
-            path.push(1);
-            let sz = self.if_false.layout(path, args);
-            path.pop();
-            sz
+        let sz = self.layout_branch(self.cond, path, args);
+
+        if self.transition.is_some() {
+            // The old view needs a layout to keep drawing.
+            let old_sz = if self.switching(path, args.cx).is_some() {
+                self.layout_branch(!self.cond, path, args)
+            } else {
+                LocalSize::zero()
+            };
+
+            let state = self.state(path, args.cx);
+            state.sizes = if self.cond {
+                [sz, old_sz]
+            } else {
+                [old_sz, sz]
+            };
         }
+
+        sz
     }
 
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
@@ -84,15 +237,13 @@ where
     }
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
-        if self.cond {
-            path.push(0);
-            self.if_true.gc(path, cx, map);
-            path.pop();
-        } else {
-            path.push(1);
-            self.if_false.gc(path, cx, map);
-            path.pop();
+        if self.transition.is_some() {
+            map.push(cx.view_id(path));
+            if self.switching(path, cx).is_some() {
+                self.gc_branch(!self.cond, path, cx, map);
+            }
         }
+        self.gc_branch(self.cond, path, cx, map);
     }
 
     fn access(
@@ -118,10 +269,11 @@ where
 impl<V0, V1> private::Sealed for Cond<V0, V1> {}
 
 /// Switches between views according to a boolean.
-pub fn cond(cond: bool, if_true: impl View, if_false: impl View) -> impl View {
+pub fn cond<V0: View, V1: View>(cond: bool, if_true: V0, if_false: V1) -> Cond<V0, V1> {
     Cond {
         cond,
         if_true,
         if_false,
+        transition: None,
     }
 }
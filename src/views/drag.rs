@@ -135,7 +135,11 @@ where
                     cx.touches[*id] = vid;
                     cx.starts[*id] = *position;
                     cx.previous_position[*id] = *position;
-                    cx.grab_cursor = self.grab;
+
+                    // Only the mouse (slot 0) has a cursor to grab.
+                    if *id == 0 {
+                        cx.grab_cursor = self.grab;
+                    }
 
                     self.func.call(
                         cx,
@@ -167,7 +171,9 @@ where
             Event::TouchEnd { id, position } => {
                 if cx.touches[*id] == vid {
                     cx.touches[*id] = ViewId::default();
-                    cx.grab_cursor = false;
+                    if *id == 0 {
+                        cx.grab_cursor = false;
+                    }
 
                     self.func.call(
                         cx,
@@ -250,14 +256,16 @@ mod tests {
             |states, _| rectangle().drag(move |cx, _delta, state, _| cx[states].push(state)),
         );
         let sz = [100.0, 100.0].into();
-        let mut path = vec![0];
+        let mut path = IdPath::root();
 
         let rect_sz = ui.layout(
             &mut path,
             &mut LayoutArgs {
                 sz,
                 cx: &mut cx,
-                text_bounds: &mut |_, _, _| LocalRect::new(LocalPoint::zero(), [90.0, 90.0].into()),
+                text_bounds: &mut TextBoundsFn(|_, _, _| {
+                    LocalRect::new(LocalPoint::zero(), [90.0, 90.0].into())
+                }),
             },
         );
         assert_eq!(path.len(), 1);
@@ -32,6 +32,7 @@ where
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        args.cx.layout_reads_env(path);
         path.push(0);
         let sz = (self.func)(args.cx.init_env(&S::default), args.cx).layout(path, args);
         path.pop();
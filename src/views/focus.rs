@@ -22,14 +22,12 @@ where
         match &event {
             Event::TouchBegin { id: _, position } => {
                 if self.hittest(path, *position, cx).is_some() {
-                    cx.focused_id = Some(vid);
-                    cx.set_dirty();
+                    cx.set_focus(Some(vid));
                 }
             }
             Event::Key(Key::Escape) => {
                 if cx.focused_id == Some(vid) {
-                    cx.focused_id = None;
-                    cx.set_dirty();
+                    cx.set_focus(None);
                 }
             }
             _ => (),
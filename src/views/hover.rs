@@ -67,11 +67,16 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
-        if let Event::TouchMove { position, .. } = &event {
-            if cx.mouse_button.is_none() {
+        match &event {
+            // Don't hover while dragging or while the cursor is grabbed.
+            Event::PointerMove { position } if cx.mouse_button.is_none() && !cx.grab_cursor => {
                 let inside = self.hittest(path, *position, cx).is_some();
                 self.func.call(cx, *position, inside, actions);
             }
+            Event::PointerLeave => {
+                self.func.call(cx, LocalPoint::zero(), false, actions);
+            }
+            _ => (),
         }
         path.push(0);
         self.child.process(event, path, cx, actions);
@@ -130,3 +135,38 @@ where
 }
 
 impl<V, F> private::Sealed for Hover<V, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_hover() {
+        let ui = state(
+            || false,
+            |hovering, _| {
+                rectangle()
+                    .size([20.0, 20.0])
+                    .hover(move |cx, inside| cx[hovering] = inside)
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<bool>();
+
+        h.hover_at([50.0, 50.0]);
+        assert!(h.cx[s]);
+
+        h.hover_at([10.0, 10.0]);
+        assert!(!h.cx[s]);
+
+        h.hover_at([50.0, 50.0]);
+        h.pointer_leave();
+        assert!(!h.cx[s]);
+
+        // No hovering while the cursor is grabbed.
+        h.cx.grab_cursor = true;
+        h.hover_at([50.0, 50.0]);
+        assert!(!h.cx[s]);
+    }
+}
@@ -48,13 +48,13 @@ mod tests {
         let ui = state(|| 0.0, |s, _| knob(s));
         let sz = [100.0, 100.0].into();
 
-        let mut path = vec![0];
+        let mut path = IdPath::root();
         let knob_sz = ui.layout(
             &mut path,
             &mut LayoutArgs {
                 sz,
                 cx: &mut cx,
-                text_bounds: &mut |_, _, _| LocalRect::zero(),
+                text_bounds: &mut TextBoundsFn(|_, _, _| LocalRect::zero()),
             },
         );
 
@@ -0,0 +1,506 @@
+use crate::*;
+use std::any::Any;
+use std::ops::Range;
+
+/// How tall the rows of a `lazy_list` are.
+///
+/// A fixed `f32` height is used as is. A function from row index to
+/// height is an estimate: rows are measured when they're laid out, and
+/// the estimate is used for rows which haven't been on screen yet.
+pub trait RowHeight: 'static {
+    /// Height of a row which hasn't been measured.
+    fn estimate(&self, index: usize) -> f32;
+
+    /// Should rows be measured when they're laid out?
+    fn measured(&self) -> bool;
+}
+
+impl RowHeight for f32 {
+    fn estimate(&self, _index: usize) -> f32 {
+        *self
+    }
+
+    fn measured(&self) -> bool {
+        false
+    }
+}
+
+impl<F: Fn(usize) -> f32 + 'static> RowHeight for F {
+    fn estimate(&self, index: usize) -> f32 {
+        self(index)
+    }
+
+    fn measured(&self) -> bool {
+        true
+    }
+}
+
+/// Per-view state for `LazyList`.
+#[derive(Default)]
+struct LazyListState {
+    /// Scroll position, if not provided by a binding.
+    position: LocalOffset,
+
+    /// Height of each row, measured or estimated. Empty for fixed heights.
+    heights: Vec<f32>,
+
+    /// Distance from the top of the list to the top of each row,
+    /// plus the total height at the end. Empty for fixed heights.
+    tops: Vec<f32>,
+
+    /// Rows laid out by the last layout.
+    visible: Range<usize>,
+}
+
+impl LazyListState {
+    /// Recomputes row tops, starting at row `from`.
+    fn update_tops(&mut self, from: usize) {
+        self.tops.resize(self.heights.len() + 1, 0.0);
+        for i in from..self.heights.len() {
+            self.tops[i + 1] = self.tops[i] + self.heights[i];
+        }
+    }
+}
+
+/// Struct for `lazy_list`. Wrapped in a `Clip`.
+pub struct LazyList<H, F, B> {
+    count: usize,
+    row_height: H,
+    func: F,
+    position: Option<B>,
+}
+
+impl<H, V, F, B> LazyList<H, F, B>
+where
+    H: RowHeight,
+    V: View,
+    F: Fn(usize) -> V + 'static,
+    B: Binding<LocalOffset>,
+{
+    fn state<'a>(&self, path: &IdPath, cx: &'a mut Context) -> &'a mut LazyListState {
+        let id = cx.init_state(path, &LazyListState::default);
+
+        // Go around get_mut since caching row heights during layout
+        // shouldn't mark anything dirty.
+        let state = cx
+            .state_map
+            .get_mut(&id)
+            .unwrap()
+            .state
+            .downcast_mut::<LazyListState>()
+            .unwrap();
+
+        // Forget rows which have been removed, and estimate rows which
+        // have been added.
+        state.visible = state.visible.start.min(self.count)..state.visible.end.min(self.count);
+        if self.row_height.measured() && state.tops.len() != self.count + 1 {
+            let old = state.heights.len().min(self.count);
+            state.heights.truncate(self.count);
+            for i in old..self.count {
+                state.heights.push(self.row_height.estimate(i));
+            }
+            state.update_tops(old);
+        }
+
+        state
+    }
+
+    /// Distance from the top of the list to the top of a row.
+    fn top(&self, state: &LazyListState, index: usize) -> f32 {
+        if self.row_height.measured() {
+            state.tops[index]
+        } else {
+            self.row_height.estimate(index) * index as f32
+        }
+    }
+
+    /// The row containing a distance from the top of the list.
+    fn row_at(&self, state: &LazyListState, y: f32) -> usize {
+        let index = if self.row_height.measured() {
+            state
+                .tops
+                .partition_point(|top| *top <= y)
+                .saturating_sub(1)
+        } else {
+            let height = self.row_height.estimate(0);
+            if height > 0.0 {
+                (y / height) as usize
+            } else {
+                0
+            }
+        };
+        index.min(self.count.saturating_sub(1))
+    }
+
+    fn position(&self, path: &IdPath, cx: &mut Context) -> LocalOffset {
+        match &self.position {
+            Some(binding) => *binding.get(cx),
+            None => self.state(path, cx).position,
+        }
+    }
+
+    fn set_position(&self, path: &IdPath, cx: &mut Context, position: LocalOffset) {
+        match &self.position {
+            Some(binding) => *binding.get_mut(cx) = position,
+            None => {
+                self.state(path, cx).position = position;
+
+                // Rows are placed during layout, so lay out again.
+                let id = cx.view_id(path);
+                cx.state_map.get_mut(&id).unwrap().dirty = true;
+                cx.set_dirty();
+            }
+        }
+    }
+
+    fn process_rows(
+        &self,
+        event: &Event,
+        visible: Range<usize>,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        for index in visible.rev() {
+            path.push(hh(&index));
+            let offset = cx.get_layout(path).offset;
+            ((self.func)(index)).process(&event.offset(-offset), path, cx, actions);
+            path.pop();
+        }
+    }
+
+    /// Limits a scroll position so the rows cover the viewport.
+    fn clamp(&self, path: &IdPath, cx: &mut Context, position: LocalOffset) -> LocalOffset {
+        let viewport = cx.get_layout(path).rect.size;
+        let state = self.state(path, cx);
+        let total = self.top(state, self.count);
+        let max_y = (total - viewport.height).max(0.0);
+        [0.0, position.y.clamp(0.0, max_y)].into()
+    }
+}
+
+impl<H, V, F, B> View for LazyList<H, F, B>
+where
+    H: RowHeight,
+    V: View,
+    F: Fn(usize) -> V + 'static,
+    B: Binding<LocalOffset>,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let viewport = cx.get_layout(path).rect;
+        let visible = self.state(path, cx).visible.clone();
+
+        match event {
+            // Rows outside the viewport can't be touched or scrolled.
+            Event::TouchBegin { position, .. } | Event::Scroll { position, .. }
+                if !viewport.contains(*position) => {}
+
+            // Nor hovered.
+            Event::PointerMove { position } if !viewport.contains(*position) => {
+                self.process_rows(&Event::PointerLeave, visible, path, cx, actions)
+            }
+
+            Event::Scroll { delta, .. } => {
+                self.process_rows(event, visible, path, cx, actions);
+
+                // Rows which scroll themselves go first.
+                if !cx.scroll_handled {
+                    let old = self.position(path, cx);
+                    let new = self.clamp(path, cx, old + LocalOffset::new(0.0, delta.y));
+                    if new != old {
+                        self.set_position(path, cx, new);
+                        cx.scroll_handled = true;
+                    }
+                }
+            }
+
+            _ => self.process_rows(event, visible, path, cx, actions),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let visible = self.state(path, args.cx).visible.clone();
+
+        for index in visible {
+            path.push(hh(&index));
+            let offset = args.cx.get_layout(path).offset;
+
+            args.renderer.save();
+            args.renderer.translate(offset);
+            ((self.func)(index)).draw(path, args);
+            args.renderer.restore();
+
+            path.pop();
+        }
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        let viewport = args.sz;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), viewport),
+                offset: LocalOffset::zero(),
+            },
+        );
+
+        let position = self.position(path, args.cx);
+        let position = self.clamp(path, args.cx, position);
+
+        let state = self.state(path, args.cx);
+        let first = self.row_at(state, position.y);
+        let mut y = self.top(state, first);
+        let mut index = first;
+        let mut changed = None;
+
+        // Lay out rows until we're past the bottom of the viewport.
+        while index < self.count && y < position.y + viewport.height {
+            let estimate = self.row_height.estimate(index);
+
+            path.push(hh(&index));
+            let child_size = ((self.func)(index))
+                .layout(path, &mut args.size([viewport.width, estimate].into()));
+
+            let height = if self.row_height.measured() && child_size.height.is_finite() {
+                child_size.height
+            } else {
+                estimate
+            };
+
+            let child_offset = align_h(
+                LocalRect::new(LocalPoint::origin(), child_size),
+                LocalRect::new(
+                    [0.0, viewport.height - (y - position.y) - height].into(),
+                    [viewport.width, height].into(),
+                ),
+                HAlignment::Center,
+            );
+            args.cx.set_layout_offset(path, child_offset);
+            path.pop();
+
+            // Cache the measured height for the rows below.
+            if self.row_height.measured() {
+                let state = self.state(path, args.cx);
+                if state.heights[index] != height {
+                    state.heights[index] = height;
+                    changed.get_or_insert(index);
+                }
+            }
+
+            y += height;
+            index += 1;
+        }
+
+        let state = self.state(path, args.cx);
+        if let Some(from) = changed {
+            state.update_tops(from);
+        }
+        state.visible = first..index;
+
+        viewport
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let id = cx.view_id(path);
+        if cx.state_map.get(&id).is_some_and(|holder| holder.dirty) {
+            // Scrolled, so every row moved.
+            let rect = cx.get_layout(path).rect;
+            cx.add_dirty_rect(rect, xform);
+            return;
+        }
+
+        let visible = self.state(path, cx).visible.clone();
+
+        for index in visible {
+            path.push(hh(&index));
+            let offset = cx.get_layout(path).offset;
+            ((self.func)(index)).dirty(path, xform.pre_translate(offset), cx);
+            path.pop();
+        }
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if !cx.get_layout(path).rect.contains(pt) {
+            return None;
+        }
+        let visible = self.state(path, cx).visible.clone();
+
+        let mut hit = None;
+        for index in visible {
+            path.push(hh(&index));
+            let offset = cx.get_layout(path).offset;
+
+            if let Some(h) = ((self.func)(index)).hittest(path, pt - offset, cx) {
+                hit = Some(h)
+            }
+            path.pop();
+        }
+        hit
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        let visible = self.state(path, cx).visible.clone();
+
+        for index in visible {
+            path.push(hh(&index));
+            ((self.func)(index)).commands(path, cx, cmds);
+            path.pop();
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        let visible = self.state(path, cx).visible.clone();
+
+        for index in visible {
+            path.push(hh(&index));
+            map.push(cx.view_id(path));
+            ((self.func)(index)).gc(path, cx, map);
+            path.pop();
+        }
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        let mut builder = accesskit::NodeBuilder::new(accesskit::Role::List);
+        let visible = self.state(path, cx).visible.clone();
+
+        let children: Vec<accesskit::NodeId> = visible
+            .filter_map(|index| {
+                path.push(hh(&index));
+                let node_id = ((self.func)(index)).access(path, cx, nodes);
+                path.pop();
+                node_id
+            })
+            .collect();
+
+        builder.set_children(children);
+        nodes.push((
+            cx.view_id(path).access_id(),
+            builder.build(&mut cx.access_node_classes),
+        ));
+        Some(cx.view_id(path).access_id())
+    }
+}
+
+impl<H, F, B> private::Sealed for LazyList<H, F, B> {}
+
+impl<H, V, F, B> Clip<LazyList<H, F, B>>
+where
+    H: RowHeight,
+    V: View,
+    F: Fn(usize) -> V + 'static,
+{
+    /// Reads and writes the scroll position through a binding. Only the
+    /// `y` component, how far the rows have been scrolled down, is used.
+    pub fn position<B2: Binding<LocalOffset>>(self, binding: B2) -> Clip<LazyList<H, F, B2>> {
+        let list = self.child;
+        Clip::new(LazyList {
+            count: list.count,
+            row_height: list.row_height,
+            func: list.func,
+            position: Some(binding),
+        })
+    }
+}
+
+/// Displays a scrolling list of `count` rows, only creating views for the
+/// rows which are on screen. See `examples/lazy_list.rs`.
+///
+/// `row_height` is either a fixed height for every row, or a function
+/// which estimates the height of a row from its index. Estimated rows
+/// are measured as they scroll into view.
+///
+/// `f` is a function called to generate a View for each visible row.
+/// Rows which scroll out of view lose their state.
+///
+/// For example:
+///
+/// ```no_run
+/// # use rui::*;
+/// rui(lazy_list(50_000, 20.0, |i| text(&format!("row {}", i))));
+/// ```
+pub fn lazy_list<H: RowHeight, V: View, F: Fn(usize) -> V + 'static>(
+    count: usize,
+    row_height: H,
+    f: F,
+) -> Clip<LazyList<H, F, StateHandle<LocalOffset>>> {
+    Clip::new(LazyList {
+        count,
+        row_height,
+        func: f,
+        position: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_lazy_list_visible_rows() {
+        let built = Rc::new(Cell::new(0));
+        let built2 = built.clone();
+        let ui = state(
+            || None,
+            move |s, _| {
+                let built = built2.clone();
+                lazy_list(100_000, 20.0, move |i| {
+                    built.set(built.get() + 1);
+                    rectangle().tap(move |cx| cx[s] = Some(i))
+                })
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<Option<usize>>();
+
+        // Only rows on screen are built, not all 100k.
+        built.set(0);
+        h.resize([100.0, 100.0]);
+        assert!(built.get() < 50, "built {} rows", built.get());
+
+        h.tap_at([50.0, 95.0]);
+        assert_eq!(h.cx[s], Some(0));
+
+        h.scroll([50.0, 50.0], [0.0, 2000.0]);
+        h.tap_at([50.0, 95.0]);
+        assert_eq!(h.cx[s], Some(100));
+
+        // Can't scroll past the end.
+        h.scroll([50.0, 50.0], [0.0, 1e9]);
+        h.tap_at([50.0, 5.0]);
+        assert_eq!(h.cx[s], Some(99_999));
+    }
+
+    #[test]
+    fn test_lazy_list_estimated_heights() {
+        let ui = state(LocalOffset::zero, |s, _| {
+            lazy_list(1000, |_| 10.0, |_| rectangle().size([100.0, 30.0])).position(s)
+        });
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<LocalOffset>();
+
+        // Rows are measured at 30 points, so the second row starts at 30.
+        let first = h.hittest([50.0, 75.0]);
+        assert!(first.is_some());
+        assert_eq!(h.hittest([50.0, 95.0]), first);
+        assert_ne!(h.hittest([50.0, 65.0]), first);
+
+        h.scroll([50.0, 50.0], [0.0, 30.0]);
+        assert_eq!(h.cx[s], LocalOffset::new(0.0, 30.0));
+        assert_ne!(h.hittest([50.0, 95.0]), first);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::*;
 use std::any::Any;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
 pub enum ListOrientation {
@@ -8,10 +9,321 @@ pub enum ListOrientation {
     Z,
 }
 
+/// Transition state of a row in a `list`.
+struct Row<ID> {
+    id: ID,
+
+    /// Seconds since the row was inserted, while it's appearing.
+    entering: Option<f32>,
+
+    /// Offset and size from the last layout.
+    layout: Option<(LocalOffset, LocalSize)>,
+
+    /// Where the row was drawn when its offset changed, and seconds since.
+    moving: Option<(LocalOffset, f32)>,
+}
+
+impl<ID> Row<ID> {
+    fn animating(&self) -> bool {
+        self.entering.is_some() || self.moving.is_some()
+    }
+
+    /// Where the row is drawn, given the offset from layout.
+    fn offset(&self, target: LocalOffset, transition: &Transition) -> LocalOffset {
+        match self.moving {
+            Some((from, elapsed)) => from.lerp(target, transition.progress(elapsed)),
+            None => target,
+        }
+    }
+}
+
+/// A row which was removed, and is drawn until it has gone.
+struct ExitingRow<ID> {
+    id: ID,
+    offset: LocalOffset,
+    size: LocalSize,
+
+    /// Seconds since the row was removed.
+    elapsed: f32,
+}
+
+/// Per-view state for a `list` with a transition.
+struct ListState<ID> {
+    /// Rows in the list, by hash of their ids.
+    rows: HashMap<u64, Row<ID>>,
+
+    exiting: Vec<ExitingRow<ID>>,
+
+    /// Hashes of the ids in order, to notice when rows move.
+    order: Vec<u64>,
+
+    /// Have the ids been seen yet? The first rows don't transition in.
+    started: bool,
+
+    /// Have the ids changed since the last layout? Rows only move
+    /// smoothly to new positions when they have.
+    changed: bool,
+
+    /// Where rows can draw until their transitions finish, as of the
+    /// last draw. Repainted on each animation frame.
+    region: Option<(LocalRect, LocalToWorld)>,
+}
+
+impl<ID> ListState<ID> {
+    fn animating(&self) -> bool {
+        !self.exiting.is_empty() || self.rows.values().any(|row| row.animating())
+    }
+}
+
 pub struct List<ID, F> {
     orientation: ListOrientation,
     ids: Vec<ID>,
     func: F,
+    transition: Option<Transition>,
+
+    /// Copies the ids of rows, which are kept while they transition.
+    /// Set along with `transition`.
+    clone_id: Option<fn(&ID) -> ID>,
+}
+
+impl<ID: Clone, F> List<ID, F> {
+    /// Animates rows as they're inserted and removed, and as they move.
+    /// Removed rows keep drawing where they were until they've gone.
+    pub fn transition(self, transition: Transition) -> Self {
+        Self {
+            transition: Some(transition),
+            clone_id: Some(ID::clone),
+            ..self
+        }
+    }
+}
+
+impl<ID, V, F> List<ID, F>
+where
+    ID: Hash + 'static,
+    V: View,
+    F: Fn(&ID) -> V + 'static,
+{
+    fn state<'a>(&self, path: &IdPath, cx: &'a Context) -> Option<&'a ListState<ID>> {
+        self.transition?;
+        cx.state_map
+            .get(&cx.view_id(path))?
+            .state
+            .downcast_ref::<ListState<ID>>()
+    }
+
+    fn state_mut<'a>(&self, path: &IdPath, cx: &'a mut Context) -> Option<&'a mut ListState<ID>> {
+        self.transition?;
+        let id = cx.view_id(path);
+
+        // Go around get_mut since animating shouldn't mark anything dirty.
+        cx.state_map
+            .get_mut(&id)?
+            .state
+            .downcast_mut::<ListState<ID>>()
+    }
+
+    /// Where a row is drawn, how much it's showing and its size.
+    /// `path` is the list's path.
+    fn row(&self, path: &mut IdPath, child: &ID, cx: &Context) -> (LocalOffset, f32, LocalSize) {
+        path.push(hh(child));
+        let layout_box = cx.get_layout(path);
+        path.pop();
+
+        let row = self
+            .state(path, cx)
+            .and_then(|state| state.rows.get(&hh(child)));
+        match (&self.transition, row) {
+            (Some(transition), Some(row)) => (
+                row.offset(layout_box.offset, transition),
+                row.entering
+                    .map(|elapsed| transition.progress(elapsed))
+                    .unwrap_or(1.0),
+                row.layout.map(|(_, size)| size).unwrap_or_default(),
+            ),
+            _ => (layout_box.offset, 1.0, layout_box.rect.size),
+        }
+    }
+
+    /// Notices inserted and removed rows and moves transitions along.
+    fn animate(
+        &self,
+        transition: Transition,
+        clone_id: fn(&ID) -> ID,
+        path: &IdPath,
+        cx: &mut Context,
+    ) {
+        cx.init_state(path, &|| ListState::<ID> {
+            rows: HashMap::new(),
+            exiting: vec![],
+            order: vec![],
+            started: false,
+            changed: false,
+            region: None,
+        });
+        let dt = cx.frame_delta();
+        let state = self.state_mut(path, cx).unwrap();
+        let was_animating = state.animating();
+
+        for row in state.rows.values_mut() {
+            if let Some(elapsed) = &mut row.entering {
+                *elapsed += dt;
+                if transition.done(*elapsed) {
+                    row.entering = None;
+                }
+            }
+            if let Some((_, elapsed)) = &mut row.moving {
+                *elapsed += dt;
+                if transition.done(*elapsed) {
+                    row.moving = None;
+                }
+            }
+        }
+        for row in &mut state.exiting {
+            row.elapsed += dt;
+        }
+        state.exiting.retain(|row| !transition.done(row.elapsed));
+
+        let order: Vec<u64> = self.ids.iter().map(hh).collect();
+        if order != state.order {
+            let keep: HashSet<u64> = order.iter().copied().collect();
+            let removed: Vec<u64> = state
+                .rows
+                .keys()
+                .filter(|key| !keep.contains(key))
+                .copied()
+                .collect();
+            for key in removed {
+                let row = state.rows.remove(&key).unwrap();
+                if let Some((offset, size)) = row.layout {
+                    state.exiting.push(ExitingRow {
+                        offset: row.offset(offset, &transition),
+                        id: row.id,
+                        size,
+                        elapsed: 0.0,
+                    });
+                }
+            }
+
+            for (key, id) in order.iter().zip(&self.ids) {
+                if !state.rows.contains_key(key) {
+                    // Put back a row which is still going.
+                    state.exiting.retain(|row| hh(&row.id) != *key);
+                    state.rows.insert(
+                        *key,
+                        Row {
+                            id: clone_id(id),
+                            entering: if state.started { Some(0.0) } else { None },
+                            layout: None,
+                            moving: None,
+                        },
+                    );
+                }
+            }
+
+            state.order = order;
+            state.changed = state.started;
+        }
+        state.started = true;
+
+        let animating = state.animating();
+        let region = state.region;
+
+        if animating {
+            cx.request_animation_frame();
+        }
+        if animating || was_animating {
+            // Only the rows move, so there's no need to update anything
+            // else. Just repaint where they are, and where they were.
+            if let Some((rect, xform)) = region {
+                cx.repaint_animation(rect, xform);
+            }
+        }
+    }
+
+    /// Remembers where rows can draw until their transitions finish.
+    /// `path` is the list's path.
+    fn record_region(&self, transition: Transition, path: &mut IdPath, args: &mut DrawArgs) {
+        let offsets: Vec<LocalOffset> = self
+            .ids
+            .iter()
+            .map(|child| {
+                path.push(hh(child));
+                let offset = args.cx.get_layout(path).offset;
+                path.pop();
+                offset
+            })
+            .collect();
+        let xform = args.renderer.current_transform();
+
+        let state = match self.state_mut(path, args.cx) {
+            Some(state) => state,
+            None => return,
+        };
+
+        let mut region: Option<LocalRect> = None;
+        let mut add = |rect: LocalRect| {
+            region = Some(region.map_or(rect, |region| region.union(&rect)));
+        };
+        for row in &state.exiting {
+            add(transition.bounds(row.size).translate(row.offset));
+        }
+        for (child, offset) in self.ids.iter().zip(offsets) {
+            if let Some(row) = state.rows.get(&hh(child)) {
+                if let (true, Some((_, size))) = (row.animating(), row.layout) {
+                    // Moving rows draw between where they were and where they're going.
+                    add(transition.bounds(size).translate(offset));
+                    if let Some((from, _)) = row.moving {
+                        add(transition.bounds(size).translate(from));
+                    }
+                }
+            }
+        }
+
+        state.region = region.map(|region| (region, xform));
+    }
+
+    /// Starts rows moving to their new offsets after the ids change.
+    fn record_layout(&self, path: &mut IdPath, sizes: &[LocalSize], cx: &mut Context) {
+        let transition = match self.transition {
+            Some(transition) => transition,
+            None => return,
+        };
+
+        let offsets: Vec<LocalOffset> = self
+            .ids
+            .iter()
+            .map(|child| {
+                path.push(hh(child));
+                let offset = cx.get_layout(path).offset;
+                path.pop();
+                offset
+            })
+            .collect();
+
+        let state = match self.state_mut(path, cx) {
+            Some(state) => state,
+            None => return,
+        };
+        let changed = std::mem::take(&mut state.changed);
+        let mut moving = false;
+
+        for ((child, offset), size) in self.ids.iter().zip(offsets).zip(sizes) {
+            if let Some(row) = state.rows.get_mut(&hh(child)) {
+                if let Some((old, _)) = row.layout {
+                    if changed && old != offset && row.entering.is_none() {
+                        row.moving = Some((row.offset(old, &transition), 0.0));
+                        moving = true;
+                    }
+                }
+                row.layout = Some((offset, *size));
+            }
+        }
+
+        if moving {
+            cx.request_animation_frame();
+        }
+    }
 }
 
 impl<ID, V, F> View for List<ID, F>
@@ -27,39 +339,81 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
+        if let (Event::Anim, Some(transition), Some(clone_id)) =
+            (event, self.transition, self.clone_id)
+        {
+            self.animate(transition, clone_id, path, cx);
+        }
+
         for child in self.ids.iter().rev() {
+            let (offset, _, _) = self.row(path, child, cx);
             path.push(hh(child));
-            let offset = cx.get_layout(path).offset;
             ((self.func)(child)).process(&event.offset(-offset), path, cx, actions);
             path.pop();
         }
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        // Removed rows go behind the others. They're taken out of
+        // the state while drawing, which needs the context.
+        if let Some(transition) = self.transition {
+            self.record_region(transition, path, args);
+
+            let exiting = match self.state_mut(path, args.cx) {
+                Some(state) => std::mem::take(&mut state.exiting),
+                None => vec![],
+            };
+            for row in &exiting {
+                path.push(hh(&row.id));
+                args.renderer.save();
+                args.renderer.translate(row.offset);
+                transition.draw(
+                    1.0 - transition.progress(row.elapsed),
+                    row.size,
+                    args,
+                    |args| ((self.func)(&row.id)).draw(path, args),
+                );
+                args.renderer.restore();
+                path.pop();
+            }
+            if let Some(state) = self.state_mut(path, args.cx) {
+                state.exiting = exiting;
+            }
+        }
+
         for child in &self.ids {
+            let (offset, amount, size) = self.row(path, child, args.cx);
             path.push(hh(child));
-            let offset = args.cx.get_layout(path).offset;
 
-            args.vger.save();
+            args.renderer.save();
 
-            args.vger.translate(offset);
+            args.renderer.translate(offset);
 
-            ((self.func)(child)).draw(path, args);
+            match &self.transition {
+                Some(transition) => transition.draw(amount, size, args, |args| {
+                    ((self.func)(child)).draw(path, args)
+                }),
+                None => ((self.func)(child)).draw(path, args),
+            }
 
-            args.vger.restore();
+            args.renderer.restore();
             path.pop();
         }
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
-        match self.orientation {
+        if let Some(size) = args.cx.cached_layout(path, args.sz) {
+            return size;
+        }
+
+        let mut sizes = Vec::<LocalSize>::new();
+        sizes.reserve(self.ids.len());
+
+        let size = match self.orientation {
             ListOrientation::Horizontal => {
                 let n = self.ids.len() as f32;
                 let proposed_child_size = LocalSize::new(args.sz.width / n, args.sz.height);
 
-                let mut sizes = Vec::<LocalSize>::new();
-                sizes.reserve(self.ids.len());
-
                 let mut width_sum = 0.0;
                 for child in &self.ids {
                     path.push(hh(child));
@@ -100,9 +454,6 @@ where
                 let n = self.ids.len() as f32;
                 let proposed_child_size = LocalSize::new(args.sz.width, args.sz.height / n);
 
-                let mut sizes = Vec::<LocalSize>::new();
-                sizes.reserve(self.ids.len());
-
                 let mut height_sum = 0.0;
                 for child in &self.ids {
                     path.push(hh(child));
@@ -144,18 +495,23 @@ where
             ListOrientation::Z => {
                 for child in &self.ids {
                     path.push(hh(child));
-                    ((self.func)(child)).layout(path, args);
+                    sizes.push(((self.func)(child)).layout(path, args));
                     path.pop();
                 }
                 args.sz
             }
-        }
+        };
+
+        self.record_layout(path, &sizes, args.cx);
+
+        args.cx.cache_layout(path, args.sz, size);
+        size
     }
 
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
         for child in &self.ids {
+            let (offset, _, _) = self.row(path, child, cx);
             path.push(hh(child));
-            let offset = cx.get_layout(path).offset;
             let xf = xform.pre_translate(offset);
             ((self.func)(child)).dirty(path, xf, cx);
             path.pop();
@@ -165,8 +521,8 @@ where
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
         let mut hit = None;
         for child in &self.ids {
+            let (offset, _, _) = self.row(path, child, cx);
             path.push(hh(child));
-            let offset = cx.get_layout(path).offset;
 
             if let Some(h) = ((self.func)(child)).hittest(path, pt - offset, cx) {
                 hit = Some(h)
@@ -192,6 +548,21 @@ where
             ((self.func)(child)).gc(path, cx, map);
             path.pop();
         }
+
+        // Keep removed rows until they've gone.
+        let exiting = match self.state_mut(path, cx) {
+            Some(state) => std::mem::take(&mut state.exiting),
+            None => vec![],
+        };
+        for row in &exiting {
+            path.push(hh(&row.id));
+            map.push(cx.view_id(path));
+            ((self.func)(&row.id)).gc(path, cx, map);
+            path.pop();
+        }
+        if let Some(state) = self.state_mut(path, cx) {
+            state.exiting = exiting;
+        }
     }
 
     fn access(
@@ -226,7 +597,8 @@ impl<ID, F> private::Sealed for List<ID, F> {}
 
 /// Displays a list of items all of which are represented by the same View. See `examples/list.rs`.
 ///
-/// `ids` is a Vec of items that implement Hash.
+/// `ids` is a Vec of items that implement Hash. They also need to
+/// implement Clone to use `transition`.
 ///
 /// `f` is a function called to generate a View for each item.
 ///
@@ -246,6 +618,8 @@ pub fn list<ID: Hash, V: View, F: Fn(&ID) -> V + 'static>(ids: Vec<ID>, f: F) ->
         orientation: ListOrientation::Vertical,
         ids,
         func: f,
+        transition: None,
+        clone_id: None,
     }
 }
 
@@ -254,6 +628,8 @@ pub fn hlist<ID: Hash, V: View, F: Fn(&ID) -> V + 'static>(ids: Vec<ID>, f: F) -
         orientation: ListOrientation::Horizontal,
         ids,
         func: f,
+        transition: None,
+        clone_id: None,
     }
 }
 
@@ -262,5 +638,7 @@ pub fn zlist<ID: Hash, V: View, F: Fn(&ID) -> V + 'static>(ids: Vec<ID>, f: F) -
         orientation: ListOrientation::Z,
         ids,
         func: f,
+        transition: None,
+        clone_id: None,
     }
 }
@@ -21,8 +21,7 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
-        let id = cx.view_id(path);
-        cx.set_state(id, self.value.clone());
+        let id = cx.set_state(path, self.value.clone());
         let s = StateHandle::new(id);
         path.push(0);
         (self.func)(s, cx).process(event, path, cx, actions);
@@ -35,16 +34,14 @@ where
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
-        let id = args.cx.view_id(path);
-        args.cx.set_state(id, self.value.clone());
+        let id = args.cx.set_state(path, self.value.clone());
         path.push(0);
         (self.func)(StateHandle::new(id), args.cx).draw(path, args);
         path.pop();
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
-        let id = args.cx.view_id(path);
-        args.cx.set_state(id, self.value.clone());
+        let id = args.cx.set_state(path, self.value.clone());
 
         path.push(0);
         let sz = (self.func)(StateHandle::new(id), args.cx).layout(path, args);
@@ -53,16 +50,14 @@ where
     }
 
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
-        let id = cx.view_id(path);
-        cx.set_state(id, self.value.clone());
+        let id = cx.set_state(path, self.value.clone());
         path.push(0);
         (self.func)(StateHandle::new(id), cx).dirty(path, xform, cx);
         path.pop();
     }
 
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
-        let id = cx.view_id(path);
-        cx.set_state(id, self.value.clone());
+        let id = cx.set_state(path, self.value.clone());
         path.push(0);
         let hit_id = (self.func)(StateHandle::new(id), cx).hittest(path, pt, cx);
         path.pop();
@@ -70,16 +65,14 @@ where
     }
 
     fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
-        let id = cx.view_id(path);
-        cx.set_state(id, self.value.clone());
+        let id = cx.set_state(path, self.value.clone());
         path.push(0);
         (self.func)(StateHandle::new(id), cx).commands(path, cx, cmds);
         path.pop();
     }
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
-        let id = cx.view_id(path);
-        cx.set_state(id, self.value.clone());
+        let id = cx.set_state(path, self.value.clone());
         map.push(id);
         path.push(0);
         (self.func)(StateHandle::new(id), cx).gc(path, cx, map);
@@ -92,8 +85,7 @@ where
         cx: &mut Context,
         nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
     ) -> Option<accesskit::NodeId> {
-        let id = cx.view_id(path);
-        cx.set_state(id, self.value.clone());
+        let id = cx.set_state(path, self.value.clone());
         path.push(0);
         let node_id = (self.func)(StateHandle::new(id), cx).access(path, cx, nodes);
         path.pop();
@@ -1,5 +1,7 @@
 mod anim;
 pub use anim::*;
+mod animated;
+pub use animated::*;
 mod anyview;
 pub use anyview::*;
 mod background;
@@ -12,6 +14,8 @@ mod clip;
 pub use clip::*;
 mod command;
 pub use command::*;
+mod command_palette;
+pub use command_palette::*;
 mod cond;
 pub use cond::*;
 mod drag;
@@ -34,6 +38,8 @@ mod key;
 pub use key::*;
 mod knob;
 pub use knob::*;
+mod lazy_list;
+pub use lazy_list::*;
 mod list;
 pub use list::*;
 mod map;
@@ -48,6 +54,8 @@ mod redux;
 pub use redux::*;
 mod role;
 pub use role::*;
+mod scroll;
+pub use scroll::*;
 mod shapes;
 pub use shapes::*;
 mod size;
@@ -63,10 +71,18 @@ mod state;
 pub use state::*;
 mod tap;
 pub use tap::*;
+mod receive;
+pub use receive::*;
+mod task;
+pub use task::*;
 mod text_editor;
 pub use text_editor::*;
+mod text_input;
+pub use text_input::*;
 mod text;
 pub use text::*;
+mod timer;
+pub use timer::*;
 mod toggle;
 pub use toggle::*;
 mod window;
@@ -25,12 +25,12 @@ where
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
-        args.vger.save();
-        args.vger.translate(self.offset);
+        args.renderer.save();
+        args.renderer.translate(self.offset);
         path.push(0);
         self.child.draw(path, args);
         path.pop();
-        args.vger.restore();
+        args.renderer.restore();
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
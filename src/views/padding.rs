@@ -25,22 +25,29 @@ where
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
-        args.vger.save();
-        args.vger.translate([self.padding, self.padding]);
+        args.renderer.save();
+        args.renderer.translate([self.padding, self.padding].into());
         path.push(0);
         self.child.draw(path, args);
         path.pop();
-        args.vger.restore();
+        args.renderer.restore();
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        if let Some(size) = args.cx.cached_layout(path, args.sz) {
+            return size;
+        }
+
         path.push(0);
         let child_size = self.child.layout(
             path,
             &mut args.size(args.sz - [2.0 * self.padding, 2.0 * self.padding].into()),
         );
         path.pop();
-        child_size + LocalSize::new(2.0 * self.padding, 2.0 * self.padding)
+        let size = child_size + LocalSize::new(2.0 * self.padding, 2.0 * self.padding);
+
+        args.cx.cache_layout(path, args.sz, size);
+        size
     }
 
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
@@ -69,6 +76,7 @@ where
     }
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
         path.push(0);
         self.child.gc(path, cx, map);
         path.pop();
@@ -0,0 +1,323 @@
+use crate::*;
+use futures::{Stream, StreamExt};
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{mpsc, Arc};
+use std::task::{Poll, Waker};
+use std::time::Duration;
+
+/// Most messages `on_receive` handles in a frame, so a sender which is
+/// faster than the UI can't stall it. The rest wait for the next frame.
+const MAX_MESSAGES_PER_FRAME: usize = 4096;
+
+/// How often `on_receive` checks a source which can't wake the event
+/// loop, once messages stop arriving.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where `on_receive` gets its messages.
+///
+/// Implemented for any `Stream` (such as
+/// `futures::channel::mpsc::UnboundedReceiver`) in a `RefCell`, and for
+/// `std::sync::mpsc::Receiver`. Wrap either in an `Rc` or `Arc` to pass
+/// it to `on_receive` each time the view is rebuilt.
+///
+/// Prefer a `Stream`: it wakes the event loop when a message arrives.
+/// A `Receiver` can't, so it's checked every frame while messages keep
+/// arriving and ten times a second otherwise. That keeps an idle app
+/// waking up, and the first message after a pause can wait 100 ms.
+pub trait MessageSource: 'static {
+    type Message;
+
+    /// Takes the next message, if there is one. Sources which can
+    /// notify should wake `waker` when the next message arrives.
+    fn try_receive(&self, waker: &Waker) -> Option<Self::Message>;
+
+    /// Does the source need checking every frame because it can't use
+    /// the waker?
+    fn needs_polling(&self) -> bool {
+        false
+    }
+}
+
+impl<T: 'static> MessageSource for mpsc::Receiver<T> {
+    type Message = T;
+
+    fn try_receive(&self, _waker: &Waker) -> Option<T> {
+        self.try_recv().ok()
+    }
+
+    fn needs_polling(&self) -> bool {
+        true
+    }
+}
+
+impl<S: Stream + Unpin + 'static> MessageSource for RefCell<S> {
+    type Message = S::Item;
+
+    fn try_receive(&self, waker: &Waker) -> Option<S::Item> {
+        match self
+            .borrow_mut()
+            .poll_next_unpin(&mut std::task::Context::from_waker(waker))
+        {
+            Poll::Ready(item) => item,
+            Poll::Pending => None,
+        }
+    }
+}
+
+impl<R: MessageSource> MessageSource for Rc<R> {
+    type Message = R::Message;
+
+    fn try_receive(&self, waker: &Waker) -> Option<R::Message> {
+        (**self).try_receive(waker)
+    }
+
+    fn needs_polling(&self) -> bool {
+        (**self).needs_polling()
+    }
+}
+
+impl<R: MessageSource> MessageSource for Arc<R> {
+    type Message = R::Message;
+
+    fn try_receive(&self, waker: &Waker) -> Option<R::Message> {
+        (**self).try_receive(waker)
+    }
+
+    fn needs_polling(&self) -> bool {
+        (**self).needs_polling()
+    }
+}
+
+/// Struct for the `on_receive` modifier.
+pub struct OnReceive<V, R, F> {
+    child: V,
+    source: R,
+    func: F,
+    coalesce: bool,
+}
+
+impl<V, R, F> OnReceive<V, R, F>
+where
+    V: View,
+    R: MessageSource,
+    F: Fn(&mut Context, R::Message) + 'static,
+{
+    pub fn new(child: V, source: R, func: F) -> Self {
+        Self {
+            child,
+            source,
+            func,
+            coalesce: false,
+        }
+    }
+
+    /// Only handles the latest message each frame, dropping the rest.
+    /// Useful for levels and positions, where only the current value
+    /// matters.
+    pub fn coalesce(self) -> Self {
+        Self {
+            coalesce: true,
+            ..self
+        }
+    }
+
+    fn receive(&self, path: &IdPath, cx: &mut Context) {
+        let waker = cx.event_loop_waker();
+        let mut latest = None;
+        let mut received = false;
+        for count in 0.. {
+            if count == MAX_MESSAGES_PER_FRAME {
+                cx.request_animation_frame();
+                break;
+            }
+            match self.source.try_receive(&waker) {
+                Some(msg) if self.coalesce => latest = Some(msg),
+                Some(msg) => (self.func)(cx, msg),
+                None => break,
+            }
+            received = true;
+        }
+        if let Some(msg) = latest {
+            (self.func)(cx, msg);
+        }
+
+        if self.source.needs_polling() {
+            if received {
+                // More are probably on the way.
+                cx.request_animation_frame();
+            } else {
+                self.check_later(path, cx);
+            }
+        }
+    }
+
+    /// Wakes up to check the source again after `IDLE_POLL_INTERVAL`,
+    /// unless a check is already due.
+    fn check_later(&self, path: &IdPath, cx: &mut Context) {
+        let id = cx.init_state(path, &|| Duration::ZERO);
+        let now = cx.now();
+
+        // Go around get_mut since waiting shouldn't mark anything dirty.
+        let next_check = cx
+            .state_map
+            .get_mut(&id)
+            .unwrap()
+            .state
+            .downcast_mut::<Duration>()
+            .unwrap();
+        if *next_check <= now {
+            *next_check = now + IDLE_POLL_INTERVAL;
+            cx.after(IDLE_POLL_INTERVAL, |_| ());
+        }
+    }
+}
+
+impl<V, R, F> View for OnReceive<V, R, F>
+where
+    V: View,
+    R: MessageSource,
+    F: Fn(&mut Context, R::Message) + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Event::Anim = event {
+            self.receive(path, cx);
+        }
+
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, R, F> private::Sealed for OnReceive<V, R, F> {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn meter(source: impl MessageSource<Message = f32> + Clone, coalesce: bool) -> impl View {
+        state(Vec::new, move |levels, _| {
+            let received =
+                EmptyView {}.on_receive(source.clone(), move |cx, level| cx[levels].push(level));
+            if coalesce {
+                received.coalesce()
+            } else {
+                received
+            }
+        })
+    }
+
+    #[test]
+    fn test_on_receive() {
+        let (send, recv) = mpsc::channel();
+        let mut h = TestHarness::new(meter(Rc::new(recv), false), [100.0, 100.0]);
+        let levels = h.root_state::<Vec<f32>>();
+
+        // Checked now and then while nothing arrives.
+        assert!(!h.cx.animating());
+        assert_eq!(h.cx.next_timer(), Some(IDLE_POLL_INTERVAL));
+        send.send(0.5).unwrap();
+        send.send(0.25).unwrap();
+        h.advance(IDLE_POLL_INTERVAL);
+        assert_eq!(h.cx[levels], vec![0.5, 0.25]);
+
+        // Then each frame while messages keep coming.
+        assert!(h.cx.animating());
+        send.send(0.75).unwrap();
+        h.advance(Duration::from_millis(16));
+        assert_eq!(h.cx[levels], vec![0.5, 0.25, 0.75]);
+        h.advance(Duration::from_millis(16));
+        assert!(!h.cx.animating());
+        assert_eq!(h.cx.next_timer(), Some(IDLE_POLL_INTERVAL));
+
+        // Only the latest message when coalescing.
+        let (send, recv) = mpsc::channel();
+        let mut h = TestHarness::new(meter(Rc::new(recv), true), [100.0, 100.0]);
+        let levels = h.root_state::<Vec<f32>>();
+        send.send(0.5).unwrap();
+        send.send(0.25).unwrap();
+        h.update();
+        assert_eq!(h.cx[levels], vec![0.25]);
+        h.update();
+        assert_eq!(h.cx[levels], vec![0.25]);
+    }
+
+    #[test]
+    fn test_on_receive_stream() {
+        let (send, recv) = futures::channel::mpsc::unbounded();
+        let mut h = TestHarness::new(meter(Rc::new(RefCell::new(recv)), false), [100.0, 100.0]);
+        let levels = h.root_state::<Vec<f32>>();
+        let woken = Arc::new(AtomicBool::new(false));
+        let woken2 = woken.clone();
+        h.cx.set_wake(move || woken2.store(true, Ordering::SeqCst));
+        h.update();
+
+        // Streams wake the event loop instead of being polled.
+        assert!(!h.cx.animating());
+        send.unbounded_send(0.5).unwrap();
+        assert!(woken.load(Ordering::SeqCst));
+        h.update();
+        assert_eq!(h.cx[levels], vec![0.5]);
+    }
+}
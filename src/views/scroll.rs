@@ -0,0 +1,387 @@
+use crate::*;
+use std::any::Any;
+
+/// Per-view state for `Scroll`.
+#[derive(Default)]
+struct ScrollState {
+    /// Scroll position, if not provided by a binding.
+    position: LocalOffset,
+
+    /// Size of the content from the last layout.
+    content: LocalSize,
+}
+
+/// Struct for `scroll`, `hscroll` and `vscroll`. Wrapped in a `Clip`.
+pub struct Scroll<V, B> {
+    child: V,
+    position: Option<B>,
+    horizontal: bool,
+    vertical: bool,
+}
+
+impl<V, B> Scroll<V, B>
+where
+    V: View,
+    B: Binding<LocalOffset>,
+{
+    fn state<'a>(&self, path: &IdPath, cx: &'a mut Context) -> &'a mut ScrollState {
+        let id = cx.init_state(path, &ScrollState::default);
+
+        // Go around get_mut since setting the content size during layout
+        // shouldn't mark anything dirty.
+        cx.state_map
+            .get_mut(&id)
+            .unwrap()
+            .state
+            .downcast_mut::<ScrollState>()
+            .unwrap()
+    }
+
+    fn position(&self, path: &IdPath, cx: &mut Context) -> LocalOffset {
+        match &self.position {
+            Some(binding) => *binding.get(cx),
+            None => self.state(path, cx).position,
+        }
+    }
+
+    fn set_position(&self, path: &IdPath, cx: &mut Context, position: LocalOffset) {
+        match &self.position {
+            Some(binding) => *binding.get_mut(cx) = position,
+            None => {
+                self.state(path, cx).position = position;
+                let id = cx.view_id(path);
+                cx.state_map.get_mut(&id).unwrap().dirty = true;
+                cx.set_dirty();
+            }
+        }
+    }
+
+    /// Limits a scroll position so the content covers the viewport.
+    fn clamp(&self, path: &IdPath, cx: &mut Context, position: LocalOffset) -> LocalOffset {
+        let viewport = cx.get_layout(path).rect.size;
+        let content = self.state(path, cx).content;
+        let max_x = if self.horizontal {
+            (content.width - viewport.width).max(0.0)
+        } else {
+            0.0
+        };
+        let max_y = if self.vertical {
+            (content.height - viewport.height).max(0.0)
+        } else {
+            0.0
+        };
+        [position.x.clamp(0.0, max_x), position.y.clamp(0.0, max_y)].into()
+    }
+
+    /// Where the content's origin is. With a zero scroll position, the
+    /// top left of the content is at the top left of the viewport.
+    fn content_offset(&self, path: &IdPath, cx: &mut Context) -> LocalOffset {
+        let viewport = cx.get_layout(path).rect.size;
+        let content = self.state(path, cx).content;
+        let position = self.position(path, cx);
+        let position = self.clamp(path, cx, position);
+        [-position.x, viewport.height - content.height + position.y].into()
+    }
+}
+
+impl<V, B> View for Scroll<V, B>
+where
+    V: View,
+    B: Binding<LocalOffset>,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        let viewport = cx.get_layout(path).rect;
+        let offset = self.content_offset(path, cx);
+
+        match event {
+            // Content outside the viewport can't be touched or scrolled.
+            Event::TouchBegin { position, .. } | Event::Scroll { position, .. }
+                if !viewport.contains(*position) => {}
+
+            // Nor hovered.
+            Event::PointerMove { position } if !viewport.contains(*position) => {
+                path.push(0);
+                self.child.process(&Event::PointerLeave, path, cx, actions);
+                path.pop();
+            }
+
+            Event::Scroll { delta, .. } => {
+                path.push(0);
+                self.child
+                    .process(&event.offset(-offset), path, cx, actions);
+                path.pop();
+
+                // The innermost scroll view which can move takes the event.
+                if !cx.scroll_handled {
+                    let old = self.position(path, cx);
+                    let new = self.clamp(path, cx, old + LocalOffset::new(-delta.x, delta.y));
+                    if new != old {
+                        self.set_position(path, cx, new);
+                        cx.scroll_handled = true;
+                    }
+                }
+            }
+
+            _ => {
+                path.push(0);
+                self.child
+                    .process(&event.offset(-offset), path, cx, actions);
+                path.pop();
+            }
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let offset = self.content_offset(path, args.cx);
+
+        args.renderer.save();
+        args.renderer.translate(offset);
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+        args.renderer.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        // Let the content be as big as it wants along the scrolling axes.
+        let proposed = LocalSize::new(
+            if self.horizontal {
+                f32::INFINITY
+            } else {
+                args.sz.width
+            },
+            if self.vertical {
+                f32::INFINITY
+            } else {
+                args.sz.height
+            },
+        );
+
+        path.push(0);
+        let mut content = self.child.layout(path, &mut args.size(proposed));
+        path.pop();
+
+        // Flexible content fills the viewport.
+        if !content.width.is_finite() {
+            content.width = args.sz.width;
+        }
+        if !content.height.is_finite() {
+            content.height = args.sz.height;
+        }
+
+        self.state(path, args.cx).content = content;
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), args.sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+        args.sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        let id = cx.view_id(path);
+        if cx.state_map.get(&id).is_some_and(|holder| holder.dirty) {
+            // Scrolled, so everything moved.
+            let rect = cx.get_layout(path).rect;
+            cx.add_dirty_rect(rect, xform);
+            return;
+        }
+
+        let offset = self.content_offset(path, cx);
+        path.push(0);
+        self.child.dirty(path, xform.pre_translate(offset), cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        if !cx.get_layout(path).rect.contains(pt) {
+            return None;
+        }
+        let offset = self.content_offset(path, cx);
+        path.push(0);
+        let vid = self.child.hittest(path, pt - offset, cx);
+        path.pop();
+        vid
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, B> private::Sealed for Scroll<V, B> {}
+
+impl<V: View, B> Clip<Scroll<V, B>> {
+    /// Reads and writes the scroll position through a binding. The position
+    /// is how far the content has been scrolled right and down.
+    pub fn position<B2: Binding<LocalOffset>>(self, binding: B2) -> Clip<Scroll<V, B2>> {
+        let scroll = self.child;
+        Clip::new(Scroll {
+            child: scroll.child,
+            position: Some(binding),
+            horizontal: scroll.horizontal,
+            vertical: scroll.vertical,
+        })
+    }
+}
+
+fn make_scroll<V: View>(
+    view: V,
+    horizontal: bool,
+    vertical: bool,
+) -> Clip<Scroll<V, StateHandle<LocalOffset>>> {
+    Clip::new(Scroll {
+        child: view,
+        position: None,
+        horizontal,
+        vertical,
+    })
+}
+
+/// Scrolls a view in both directions when it's bigger than the available space.
+///
+/// When scroll views are nested, the innermost one under the pointer
+/// which can still move takes each scroll event.
+pub fn scroll<V: View>(view: V) -> Clip<Scroll<V, StateHandle<LocalOffset>>> {
+    make_scroll(view, true, true)
+}
+
+/// Scrolls a view horizontally.
+pub fn hscroll<V: View>(view: V) -> Clip<Scroll<V, StateHandle<LocalOffset>>> {
+    make_scroll(view, true, false)
+}
+
+/// Scrolls a view vertically.
+pub fn vscroll<V: View>(view: V) -> Clip<Scroll<V, StateHandle<LocalOffset>>> {
+    make_scroll(view, false, true)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn rows() -> impl View {
+        vstack((
+            rectangle().size([100.0, 100.0]),
+            rectangle().size([100.0, 100.0]),
+            rectangle().size([100.0, 100.0]),
+        ))
+    }
+
+    #[test]
+    fn test_vscroll() {
+        let ui = state(LocalOffset::zero, |s, _| vscroll(rows()).position(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<LocalOffset>();
+
+        let top = h.hittest([50.0, 50.0]);
+        assert!(top.is_some());
+
+        // Move the content up by a row.
+        h.scroll([50.0, 50.0], [0.0, 100.0]);
+        assert_eq!(h.cx[s], LocalOffset::new(0.0, 100.0));
+        assert!(h.hittest([50.0, 50.0]).is_some());
+        assert_ne!(h.hittest([50.0, 50.0]), top);
+
+        // Can't scroll past the end, or sideways.
+        h.scroll([50.0, 50.0], [-50.0, 500.0]);
+        assert_eq!(h.cx[s], LocalOffset::new(0.0, 200.0));
+
+        h.scroll([50.0, 50.0], [0.0, -500.0]);
+        assert_eq!(h.cx[s], LocalOffset::zero());
+    }
+
+    #[test]
+    fn test_scroll_own_state() {
+        let ui = scroll(rows());
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let top = h.hittest([50.0, 50.0]);
+
+        h.scroll([50.0, 50.0], [0.0, 150.0]);
+        assert_ne!(h.hittest([50.0, 50.0]), top);
+
+        // Outside the window, nothing happens.
+        h.scroll([500.0, 500.0], [0.0, -150.0]);
+        assert_ne!(h.hittest([50.0, 50.0]), top);
+    }
+
+    #[test]
+    fn test_nested_scroll() {
+        let ui = state(LocalOffset::zero, |outer, _| {
+            vscroll(vstack((
+                vscroll(rows()).size([100.0, 100.0]),
+                rectangle().size([100.0, 100.0]),
+            )))
+            .position(outer)
+        });
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let outer = h.root_state::<LocalOffset>();
+
+        // The inner view scrolls until it reaches the end.
+        h.scroll([50.0, 50.0], [0.0, 150.0]);
+        h.scroll([50.0, 50.0], [0.0, 150.0]);
+        assert_eq!(h.cx[outer], LocalOffset::zero());
+
+        h.scroll([50.0, 50.0], [0.0, 50.0]);
+        assert_eq!(h.cx[outer], LocalOffset::new(0.0, 50.0));
+    }
+
+    #[test]
+    fn test_scroll_clips_pointer() {
+        let ui = state(
+            || false,
+            |hovered, _| {
+                vscroll(vstack((
+                    rectangle().size([100.0, 100.0]),
+                    rectangle()
+                        .size([100.0, 100.0])
+                        .hover(move |cx, inside| cx[hovered] = inside),
+                )))
+                .size([100.0, 100.0])
+            },
+        );
+        let mut h = TestHarness::new(ui, [100.0, 300.0]);
+        let hovered = h.root_state::<bool>();
+
+        // The second row is below the viewport.
+        assert_eq!(h.hittest([50.0, 50.0]), None);
+        h.hover_at([50.0, 50.0]);
+        assert!(!h.cx[hovered]);
+
+        h.scroll([50.0, 150.0], [0.0, 100.0]);
+        h.hover_at([50.0, 150.0]);
+        assert!(h.cx[hovered]);
+        h.hover_at([50.0, 50.0]);
+        assert!(!h.cx[hovered]);
+    }
+}
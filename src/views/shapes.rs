@@ -22,10 +22,13 @@ impl Circle {
 
 impl View for Circle {
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        if !args.is_visible(args.cx.get_layout(path).rect) {
+            return;
+        }
         let (center, radius) = self.geom(path, args.cx);
 
-        let vger = &mut args.vger;
-        let paint = self.paint.vger_paint(vger);
+        let vger = &mut *args.renderer;
+        let paint = self.paint.renderer_paint(vger);
         vger.fill_circle(center, radius, paint);
     }
 
@@ -96,9 +99,12 @@ impl Rectangle {
 impl View for Rectangle {
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         let rect = self.geom(path, args.cx);
+        if !args.is_visible(rect) {
+            return;
+        }
 
-        let vger = &mut args.vger;
-        let paint = self.paint.vger_paint(vger);
+        let vger = &mut *args.renderer;
+        let paint = self.paint.renderer_paint(vger);
         vger.fill_rect(rect, self.corner_radius, paint);
     }
 
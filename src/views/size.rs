@@ -33,9 +33,15 @@ where
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        if let Some(size) = args.cx.cached_layout(path, args.sz) {
+            return size;
+        }
+
         path.push(0);
         self.child.layout(path, &mut args.size(self.size));
         path.pop();
+
+        args.cx.cache_layout(path, args.sz, self.size);
         self.size
     }
 
@@ -59,6 +65,7 @@ where
     }
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
         path.push(0);
         self.child.gc(path, cx, map);
         path.pop();
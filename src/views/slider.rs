@@ -55,7 +55,7 @@ pub fn hslider(value: impl Binding<f32>) -> impl SliderMods {
                         paint,
                     );
                     let paint = vger.color_paint(opts.thumb);
-                    vger.fill_circle([x, c.y], r, paint);
+                    vger.fill_circle([x, c.y].into(), r, paint);
                 })
                 .geom(move |cx, sz, _| {
                     if sz.width != cx[width] {
@@ -105,7 +105,7 @@ pub fn vslider(
                         paint,
                     );
                     let paint = vger.color_paint(opts.thumb);
-                    vger.fill_circle([c.x, y], SLIDER_THUMB_RADIUS, paint);
+                    vger.fill_circle([c.x, y].into(), SLIDER_THUMB_RADIUS, paint);
                 })
                 .geom(move |cx, sz, _| {
                     if sz.height != cx[height] {
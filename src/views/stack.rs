@@ -58,16 +58,16 @@ impl<VT: ViewTuple + 'static, D: StackDirection + 'static> View for Stack<VT, D>
             path.push(c);
             let layout_box = args.cx.get_layout(path);
 
-            args.vger.save();
+            args.renderer.save();
 
-            args.vger.translate(layout_box.offset);
+            args.renderer.translate(layout_box.offset);
 
             (*child).draw(path, args);
             c += 1;
 
             if DEBUG_LAYOUT {
-                let paint = args.vger.color_paint(CONTROL_BACKGROUND);
-                args.vger.stroke_rect(
+                let paint = args.renderer.color_paint(CONTROL_BACKGROUND);
+                args.renderer.stroke_rect(
                     layout_box.rect.min(),
                     layout_box.rect.max(),
                     0.0,
@@ -78,14 +78,18 @@ impl<VT: ViewTuple + 'static, D: StackDirection + 'static> View for Stack<VT, D>
 
             path.pop();
 
-            args.vger.restore();
+            args.renderer.restore();
         })
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        if let Some(size) = args.cx.cached_layout(path, args.sz) {
+            return size;
+        }
+
         let n = self.children.len() as f32;
 
-        match D::ORIENTATION {
+        let size = match D::ORIENTATION {
             StackOrientation::Horizontal => {
                 let proposed_child_size = LocalSize::new(args.sz.width / n, args.sz.height);
 
@@ -198,7 +202,10 @@ impl<VT: ViewTuple + 'static, D: StackDirection + 'static> View for Stack<VT, D>
                 });
                 args.sz
             }
-        }
+        };
+
+        args.cx.cache_layout(path, args.sz, size);
+        size
     }
 
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
@@ -61,24 +61,21 @@ where
         cx: &mut Context,
         actions: &mut Vec<Box<dyn Any>>,
     ) {
-        let id = cx.view_id(path);
-        cx.init_state(id, &self.default);
+        let id = cx.init_state(path, &self.default);
         path.push(0);
         (self.func)(StateHandle::new(id), cx).process(event, path, cx, actions);
         path.pop();
     }
 
     fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
-        let id = args.cx.view_id(path);
-        args.cx.init_state(id, &self.default);
+        let id = args.cx.init_state(path, &self.default);
         path.push(0);
         (self.func)(StateHandle::new(id), args.cx).draw(path, args);
         path.pop();
     }
 
     fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
-        let id = args.cx.view_id(path);
-        args.cx.init_state(id, &self.default);
+        let id = args.cx.init_state(path, &self.default);
 
         // Do we need to recompute layout?
         let mut compute_layout = true;
@@ -94,7 +91,7 @@ where
                 }
             }
 
-            compute_layout = any_dirty;
+            compute_layout = any_dirty || args.cx.reads_env(path);
         }
 
         if compute_layout {
@@ -127,24 +124,12 @@ where
     }
 
     fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
-        let default = &self.default;
-        let id = cx.view_id(path);
-        let holder = cx.state_map.entry(id).or_insert_with(|| StateHolder {
-            state: Box::new((default)()),
-            dirty: false,
-        });
-
-        if holder.dirty {
+        let id = cx.init_state(path, &self.default);
+
+        if cx.is_dirty(id) {
             // Add a region.
             let rect = cx.get_layout(path).rect;
-            let pts: [LocalPoint; 4] = [
-                rect.min(),
-                [rect.max_x(), rect.min_y()].into(),
-                [rect.min_x(), rect.max_y()].into(),
-                rect.max(),
-            ];
-            let world_pts = pts.map(|p| xform.transform_point(p));
-            cx.dirty_region.add_rect(WorldRect::from_points(world_pts));
+            cx.add_dirty_rect(rect, xform);
         } else {
             path.push(0);
             (self.func)(StateHandle::new(id), cx).dirty(path, xform, cx);
@@ -153,8 +138,7 @@ where
     }
 
     fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
-        let id = cx.view_id(path);
-        cx.init_state(id, &self.default);
+        let id = cx.init_state(path, &self.default);
         path.push(0);
         let hit_id = (self.func)(StateHandle::new(id), cx).hittest(path, pt, cx);
         path.pop();
@@ -162,16 +146,14 @@ where
     }
 
     fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
-        let id = cx.view_id(path);
-        cx.init_state(id, &self.default);
+        let id = cx.init_state(path, &self.default);
         path.push(0);
         (self.func)(StateHandle::new(id), cx).commands(path, cx, cmds);
         path.pop();
     }
 
     fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
-        let id = cx.view_id(path);
-        cx.init_state(id, &self.default);
+        let id = cx.init_state(path, &self.default);
         map.push(id);
         path.push(0);
         (self.func)(StateHandle::new(id), cx).gc(path, cx, map);
@@ -184,8 +166,7 @@ where
         cx: &mut Context,
         nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
     ) -> Option<accesskit::NodeId> {
-        let id = cx.view_id(path);
-        cx.init_state(id, &self.default);
+        let id = cx.init_state(path, &self.default);
         path.push(0);
         let node_id = (self.func)(StateHandle::new(id), cx).access(path, cx, nodes);
         path.pop();
@@ -0,0 +1,109 @@
+use crate::*;
+use std::any::Any;
+use std::future::Future;
+use std::hash::Hash;
+
+/// Struct for the `task` modifier.
+pub struct TaskView<V, K, B, F> {
+    child: V,
+    key: K,
+    binding: B,
+    func: F,
+}
+
+impl<V, K, B, F> TaskView<V, K, B, F> {
+    pub fn new(child: V, key: K, binding: B, func: F) -> Self {
+        Self {
+            child,
+            key,
+            binding,
+            func,
+        }
+    }
+}
+
+impl<V, K, T, B, Fut, F> View for TaskView<V, K, B, F>
+where
+    V: View,
+    K: Hash + 'static,
+    T: 'static,
+    B: Binding<T>,
+    Fut: Future<Output = T> + 'static,
+    F: Fn(&K) -> Fut + 'static,
+{
+    fn process(
+        &self,
+        event: &Event,
+        path: &mut IdPath,
+        cx: &mut Context,
+        actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if let Event::Anim = event {
+            let id = cx.view_id(path);
+            let binding = self.binding;
+            cx.view_task(
+                id,
+                hh(&self.key),
+                || (self.func)(&self.key),
+                move |cx, value| *binding.get_mut(cx) = value,
+            );
+        }
+
+        path.push(0);
+        self.child.process(event, path, cx, actions);
+        path.pop();
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        path.push(0);
+        self.child.draw(path, args);
+        path.pop();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        path.push(0);
+        let sz = self.child.layout(path, args);
+        path.pop();
+        sz
+    }
+
+    fn dirty(&self, path: &mut IdPath, xform: LocalToWorld, cx: &mut Context) {
+        path.push(0);
+        self.child.dirty(path, xform, cx);
+        path.pop();
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        path.push(0);
+        let id = self.child.hittest(path, pt, cx);
+        path.pop();
+        id
+    }
+
+    fn commands(&self, path: &mut IdPath, cx: &mut Context, cmds: &mut Vec<CommandInfo>) {
+        path.push(0);
+        self.child.commands(path, cx, cmds);
+        path.pop();
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+        path.push(0);
+        self.child.gc(path, cx, map);
+        path.pop();
+    }
+
+    fn access(
+        &self,
+        path: &mut IdPath,
+        cx: &mut Context,
+        nodes: &mut Vec<(accesskit::NodeId, accesskit::Node)>,
+    ) -> Option<accesskit::NodeId> {
+        path.push(0);
+        let node_id = self.child.access(path, cx, nodes);
+        path.pop();
+        node_id
+    }
+}
+
+impl<V, K, B, F> private::Sealed for TaskView<V, K, B, F> {}
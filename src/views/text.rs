@@ -25,22 +25,26 @@ impl Text {
 }
 
 impl View for Text {
-    fn draw(&self, _path: &mut IdPath, args: &mut DrawArgs) {
-        let vger = &mut args.vger;
-        let origin = vger.text_bounds(self.text.as_str(), self.size, None).origin;
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let origin = args.cx.get_layout(path).rect.origin;
+        let vger = &mut *args.renderer;
 
         vger.save();
-        vger.translate([-origin.x, -origin.y]);
+        vger.translate([-origin.x, -origin.y].into());
         vger.text(self.text.as_str(), self.size, self.color, None);
         vger.restore();
     }
-    fn layout(&self, _path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
-        (args.text_bounds)(self.text.as_str(), self.size, None).size
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        text_layout(self.text.as_str(), self.size, path, args)
     }
     fn hittest(&self, _path: &mut IdPath, _pt: LocalPoint, _cx: &mut Context) -> Option<ViewId> {
         None
     }
 
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+
     fn access(
         &self,
         path: &mut IdPath,
@@ -74,6 +78,19 @@ impl TextModifiers for Text {
 
 impl private::Sealed for Text {}
 
+/// Measures text and stores its bounds so drawing can line up with layout.
+fn text_layout(text: &str, size: u32, path: &IdPath, args: &mut LayoutArgs) -> LocalSize {
+    let rect = args.text_bounds.text_bounds(text, size, None);
+    args.cx.update_layout(
+        path,
+        LayoutBox {
+            rect,
+            offset: LocalOffset::zero(),
+        },
+    );
+    rect.size
+}
+
 /// Shows a string as a label (not editable).
 pub fn text(name: &str) -> Text {
     Text {
@@ -87,19 +104,23 @@ impl<V> View for V
 where
     V: std::fmt::Display + std::fmt::Debug + 'static,
 {
-    fn draw(&self, _path: &mut IdPath, args: &mut DrawArgs) {
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
         let txt = &format!("{}", self);
-        let vger = &mut args.vger;
-        let origin = vger.text_bounds(txt, Text::DEFAULT_SIZE, None).origin;
+        let origin = args.cx.get_layout(path).rect.origin;
+        let vger = &mut *args.renderer;
 
         vger.save();
-        vger.translate([-origin.x, -origin.y]);
+        vger.translate([-origin.x, -origin.y].into());
         vger.text(txt, Text::DEFAULT_SIZE, TEXT_COLOR, None);
         vger.restore();
     }
-    fn layout(&self, _path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
         let txt = &format!("{}", self);
-        (args.text_bounds)(txt, Text::DEFAULT_SIZE, None).size
+        text_layout(txt, Text::DEFAULT_SIZE, path, args)
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
     }
 
     fn access(
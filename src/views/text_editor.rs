@@ -1,12 +1,28 @@
 use crate::*;
+use std::any::Any;
 
 /// View-model for `text_editor`.
 struct TextEditorState {
+    /// Index of the character the caret is before.
     cursor: usize,
+
+    /// Text an input method is composing at the cursor.
+    preedit: String,
+
+    /// Selection within `preedit`, in bytes.
+    preedit_cursor: Option<(usize, usize)>,
+
     glyph_rects: Vec<LocalRect>,
     lines: Vec<LineMetrics>,
 }
 
+/// Byte offset of the character at `index`, or the end of `text`.
+fn byte_offset(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
 impl TextEditorState {
     fn fwd(&mut self, len: usize) {
         self.cursor += 1;
@@ -78,7 +94,7 @@ impl TextEditorState {
                 text
             }
             Key::ArrowRight => {
-                self.fwd(text.len());
+                self.fwd(text.chars().count());
                 text
             }
             Key::ArrowUp => {
@@ -92,89 +108,301 @@ impl TextEditorState {
             Key::Backspace => {
                 if self.cursor > 0 {
                     let mut t = text;
-                    t.remove(self.cursor - 1);
+                    t.remove(byte_offset(&t, self.cursor - 1));
                     self.back();
                     t
                 } else {
                     text
                 }
             }
-            Key::Character(c) => {
-                let mut t = text;
-                t.insert_str(self.cursor, &format!("{}", c));
-                self.cursor += 1;
-                t
-            }
-            Key::Space => {
-                let mut t = text;
-                t.insert(self.cursor, ' ');
-                self.cursor += 1;
-                t
-            }
             Key::Home => {
                 self.cursor = 0;
                 text
             }
             Key::End => {
-                self.cursor = text.len();
+                self.cursor = text.chars().count();
                 text
             }
             _ => text,
         }
     }
+
+    /// Inserts typed or committed text at the cursor.
+    fn insert(&mut self, text: String, input: &str) -> String {
+        let mut t = text;
+        t.insert_str(byte_offset(&t, self.cursor), input);
+        self.cursor += input.chars().count();
+        self.preedit.clear();
+        t
+    }
+
+    /// The text with any composition shown at the cursor.
+    fn display_text(&self, text: &str) -> String {
+        let mut t = text.to_string();
+        t.insert_str(byte_offset(&t, self.cursor), &self.preedit);
+        t
+    }
+
+    /// Index of the glyph the caret is drawn before.
+    fn caret(&self) -> usize {
+        let composed = match self.preedit_cursor {
+            Some((_, end)) => self.preedit.get(..end).unwrap_or(&self.preedit),
+            None => &self.preedit,
+        };
+        self.cursor + composed.chars().count()
+    }
 }
 
 impl TextEditorState {
     fn new() -> Self {
         Self {
             cursor: 0,
+            preedit: String::new(),
+            preedit_cursor: None,
             glyph_rects: vec![],
             lines: vec![],
         }
     }
 }
 
+const FONT_SIZE: u32 = 18;
+
+/// The editor once its state and focus are known. Text is measured
+/// during layout so cursor movement doesn't depend on drawing.
+struct TextEditor<B> {
+    text: B,
+    state: StateHandle<TextEditorState>,
+    has_focus: bool,
+}
+
+impl<B> View for TextEditor<B>
+where
+    B: Binding<String>,
+{
+    fn process(
+        &self,
+        event: &Event,
+        _path: &mut IdPath,
+        cx: &mut Context,
+        _actions: &mut Vec<Box<dyn Any>>,
+    ) {
+        if !self.has_focus {
+            return;
+        }
+        match event {
+            Event::Key(k) => {
+                let t = self.text.with(cx, |t| t.clone());
+                let new_t = cx[self.state].key(k, t);
+                self.text.with_mut(cx, |t| *t = new_t);
+            }
+            Event::TextInput(input) => {
+                let t = self.text.with(cx, |t| t.clone());
+                let new_t = cx[self.state].insert(t, input);
+                self.text.with_mut(cx, |t| *t = new_t);
+            }
+            Event::ImeComposition { preedit, cursor } => {
+                let s = &mut cx[self.state];
+                s.preedit = preedit.clone();
+                s.preedit_cursor = *cursor;
+            }
+            _ => (),
+        }
+    }
+
+    fn draw(&self, path: &mut IdPath, args: &mut DrawArgs) {
+        let rect = args.cx.get_layout(path).rect;
+        let vger = &mut *args.renderer;
+        vger.save();
+        vger.translate([0.0, rect.height()].into());
+        let break_width = Some(rect.width());
+
+        let s = &args.cx[self.state];
+        let shown = s.display_text(self.text.get(args.cx));
+        vger.text(&shown, FONT_SIZE, TEXT_COLOR, break_width);
+
+        if self.has_focus {
+            let rects = &s.glyph_rects;
+
+            // Underline the composition.
+            let preedit_len = s.preedit.chars().count();
+            let start = s.cursor.min(rects.len());
+            let composing = start..(start + preedit_len).min(rects.len());
+            let underline_paint = vger.color_paint(TEXT_COLOR);
+            for r in &rects[composing] {
+                let underline = LocalRect::new(
+                    [r.origin.x, r.origin.y - 2.0].into(),
+                    [r.size.width, 1.0].into(),
+                );
+                vger.fill_rect(underline, 0.0, underline_paint);
+            }
+
+            let caret = s.caret();
+            let glyph_rect_paint = vger.color_paint(vger::Color::MAGENTA);
+            let p = if caret >= rects.len() {
+                if let Some(r) = rects.last() {
+                    [r.origin.x + r.size.width, r.origin.y].into()
+                } else {
+                    [0.0, -20.0].into()
+                }
+            } else {
+                rects[caret].origin
+            };
+            let caret_rect = LocalRect::new(p, [2.0, 20.0].into());
+            vger.fill_rect(caret_rect, 0.0, glyph_rect_paint);
+
+            args.cx.ime_rect = Some(vger.current_transform().outer_transformed_rect(&caret_rect));
+        }
+        vger.restore();
+    }
+
+    fn layout(&self, path: &mut IdPath, args: &mut LayoutArgs) -> LocalSize {
+        args.cx.update_layout(
+            path,
+            LayoutBox {
+                rect: LocalRect::new(LocalPoint::zero(), args.sz),
+                offset: LocalOffset::zero(),
+            },
+        );
+
+        let break_width = Some(args.sz.width);
+        let text = args.cx[self.state].display_text(self.text.get(args.cx));
+        let glyph_rects = args
+            .text_bounds
+            .glyph_positions(&text, FONT_SIZE, break_width);
+        let lines = args.text_bounds.line_metrics(&text, FONT_SIZE, break_width);
+
+        // Don't dirty the state, since layout would then run again.
+        if let Some(holder) = args.cx.state_map.get_mut(&self.state.id) {
+            if let Some(s) = holder.state.downcast_mut::<TextEditorState>() {
+                s.glyph_rects = glyph_rects;
+                s.lines = lines;
+            }
+        }
+
+        args.sz
+    }
+
+    fn hittest(&self, path: &mut IdPath, pt: LocalPoint, cx: &mut Context) -> Option<ViewId> {
+        let rect = cx.get_layout(path).rect;
+
+        if rect.contains(pt) {
+            Some(cx.view_id(path))
+        } else {
+            None
+        }
+    }
+
+    fn gc(&self, path: &mut IdPath, cx: &mut Context, map: &mut Vec<ViewId>) {
+        map.push(cx.view_id(path));
+    }
+}
+
+impl<B> private::Sealed for TextEditor<B> {}
+
 /// A multi-line text editor.
 ///
-/// This shows how a complex View with internal
-/// state can be created from more atomic Views.
+/// Shows the text, and edits it with the keyboard and input methods
+/// while focused.
 pub fn text_editor(text: impl Binding<String>) -> impl View {
     focus(move |has_focus| {
-        state(TextEditorState::new, move |state, cx| {
-            let cursor = cx[state].cursor;
-            canvas(move |cx, rect, vger| {
-                vger.translate([0.0, rect.height()]);
-                let font_size = 18;
-                let break_width = Some(rect.width());
-
-                vger.text(text.get(cx), font_size, TEXT_COLOR, break_width);
-
-                if has_focus {
-                    let rects = vger.glyph_positions(text.get(cx), font_size, break_width);
-                    let lines = vger.line_metrics(text.get(cx), font_size, break_width);
-                    let glyph_rect_paint = vger.color_paint(vger::Color::MAGENTA);
-                    let p = if cursor == rects.len() {
-                        if let Some(r) = rects.last() {
-                            [r.origin.x + r.size.width, r.origin.y].into()
-                        } else {
-                            [0.0, -20.0].into()
-                        }
-                    } else {
-                        rects[cursor].origin
-                    };
-                    vger.fill_rect(LocalRect::new(p, [2.0, 20.0].into()), 0.0, glyph_rect_paint);
-
-                    cx[state].glyph_rects = rects;
-                    cx[state].lines = lines;
-                }
-            })
-            .key(move |cx, k| {
-                if has_focus {
-                    let t = text.with(cx, |t| t.clone());
-                    let new_t = cx[state].key(&k, t);
-                    text.with_mut(cx, |t| *t = new_t);
-                }
-            })
+        state(TextEditorState::new, move |state, _| TextEditor {
+            text,
+            state,
+            has_focus,
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_text_editor() {
+        let ui = state(String::new, |s, _| text_editor(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<String>();
+
+        // Keys are ignored until the editor has focus.
+        h.type_text("a");
+        assert_eq!(h.cx[s], "");
+
+        h.tap_at([50.0, 50.0]);
+        h.type_text("ac");
+        h.send_key(Key::ArrowLeft);
+        h.type_text("b");
+        assert_eq!(h.cx[s], "abc");
+    }
+
+    #[test]
+    fn test_text_editor_modifiers() {
+        let ui = state(String::new, |s, _| text_editor(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<String>();
+
+        // Shortcuts don't type, but AltGr, reported as Ctrl+Alt, does.
+        h.tap_at([50.0, 50.0]);
+        h.cx.key_mods.control = true;
+        h.type_text("z");
+        assert_eq!(h.cx[s], "");
+        h.cx.key_mods.alt = true;
+        h.type_text("@€");
+        assert_eq!(h.cx[s], "@€");
+    }
+
+    #[test]
+    fn test_text_editor_lines() {
+        let ui = state(|| "abc\ndef".to_string(), |s, _| text_editor(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<String>();
+
+        h.tap_at([50.0, 50.0]);
+        h.send_key(Key::ArrowRight);
+        h.send_key(Key::ArrowDown);
+        h.type_text("x");
+        assert_eq!(h.cx[s], "abc\ndxef");
+    }
+
+    #[test]
+    fn test_text_editor_input() {
+        let ui = state(String::new, |s, _| text_editor(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+        let s = h.root_state::<String>();
+
+        h.tap_at([50.0, 50.0]);
+        h.input_text("é");
+        h.input_text("日本");
+        h.send_key(Key::ArrowLeft);
+        h.send_key(Key::Backspace);
+        assert_eq!(h.cx[s], "é本");
+
+        // Compositions are shown but not part of the text until committed.
+        h.compose("ka", None);
+        h.compose("か", Some((3, 3)));
+        assert_eq!(h.cx[s], "é本");
+        h.input_text("か");
+        assert_eq!(h.cx[s], "éか本");
+        h.send_key(Key::End);
+        h.type_text("!");
+        assert_eq!(h.cx[s], "éか本!");
+    }
+
+    #[test]
+    fn test_text_editor_ime_rect() {
+        let ui = state(String::new, |s, _| text_editor(s));
+        let mut h = TestHarness::new(ui, [100.0, 100.0]);
+
+        h.draw();
+        assert_eq!(h.cx.ime_rect, None);
+
+        h.tap_at([50.0, 50.0]);
+        h.draw();
+        let rect = h.cx.ime_rect.unwrap();
+        assert!(rect.min_y() > 0.0 && rect.max_y() <= 100.0);
+
+        h.send_key(Key::Escape);
+        h.draw();
+        assert_eq!(h.cx.ime_rect, None);
+    }
+}
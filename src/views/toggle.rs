@@ -37,13 +37,13 @@ mod tests {
         let ui = state(|| false, |s, _| toggle(s));
         let sz = [40.0, 20.0].into();
 
-        let mut path = vec![0];
+        let mut path = IdPath::root();
         let knob_sz = ui.layout(
             &mut path,
             &mut LayoutArgs {
                 sz,
                 cx: &mut cx,
-                text_bounds: &mut |_, _, _| LocalRect::zero(),
+                text_bounds: &mut TextBoundsFn(|_, _, _| LocalRect::zero()),
             },
         );
 
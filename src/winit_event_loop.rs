@@ -2,20 +2,23 @@ use crate::*;
 
 use futures::executor::block_on;
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::VecDeque,
     sync::{Arc, Mutex},
 };
 
 use winit::{
-    dpi::PhysicalSize,
+    dpi::{LogicalPosition, PhysicalSize},
     event::{
-        ElementState, Event as WEvent, MouseButton as WMouseButton, Touch, TouchPhase,
-        VirtualKeyCode, WindowEvent,
+        ElementState, Event as WEvent, Ime, MouseButton as WMouseButton, MouseScrollDelta, Touch,
+        TouchPhase, VirtualKeyCode, WindowEvent,
     },
     event_loop::{ControlFlow, EventLoop, EventLoopProxy},
     window::{Window, WindowBuilder},
 };
 
+/// How far one line of a scroll wheel scrolls, in points.
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
 type WorkQueue = VecDeque<Box<dyn FnOnce(&mut Context) + Send>>;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -30,9 +33,25 @@ lazy_static! {
 pub fn on_main(f: impl FnOnce(&mut Context) + Send + 'static) {
     GLOBAL_WORK_QUEUE.lock().unwrap().push_back(Box::new(f));
 
-    // Wake up the event loop.
-    let opt_proxy = GLOBAL_EVENT_LOOP_PROXY.lock().unwrap();
-    if let Some(proxy) = &*opt_proxy {
+    wake_event_loop();
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// Everything runs on one thread, so the proxy needn't be shared.
+    static EVENT_LOOP_PROXY: std::cell::RefCell<Option<EventLoopProxy<()>>> =
+        std::cell::RefCell::new(None);
+}
+
+/// Wakes up the event loop, so it updates.
+fn wake_event_loop() {
+    #[cfg(not(target_arch = "wasm32"))]
+    let opt_proxy = GLOBAL_EVENT_LOOP_PROXY.lock().unwrap().clone();
+
+    #[cfg(target_arch = "wasm32")]
+    let opt_proxy = EVENT_LOOP_PROXY.with(|proxy| proxy.borrow().clone());
+
+    if let Some(proxy) = opt_proxy {
         if let Err(err) = proxy.send_event(()) {
             println!("error waking up event loop: {:?}", err);
         }
@@ -111,9 +130,10 @@ async fn setup(window: &Window) -> Setup {
     }
 }
 
-fn process_event(cx: &mut Context, view: &impl View, event: &Event, window: &Window) {
-    cx.process(view, event);
+fn process_event(host: &mut RuiHost<impl View>, event: Event, window: &Window) {
+    host.handle_event(event);
 
+    let cx = &mut host.cx;
     if cx.grab_cursor && !cx.prev_grab_cursor {
         println!("grabbing cursor");
         window
@@ -136,6 +156,14 @@ fn process_event(cx: &mut Context, view: &impl View, event: &Event, window: &Win
 
 /// Call this function to run your UI.
 pub fn rui(view: impl View) {
+    rui_with_commands(view, |_| ())
+}
+
+/// Version of `rui` which calls `on_commands` with the menu commands
+/// whenever they change, so an app can build native menus. Hotkeys
+/// are handled already. Use `Context::send_command` from `on_main`
+/// when a menu item is chosen.
+pub fn rui_with_commands(view: impl View, mut on_commands: impl FnMut(&[CommandInfo]) + 'static) {
     let event_loop = EventLoop::new();
 
     let mut window_title = String::from("rui");
@@ -149,9 +177,14 @@ pub fn rui(view: impl View) {
     let adapter = setup.adapter;
     let queue = Arc::new(setup.queue);
 
+    // Copying into the surface lets us repaint only what changed.
+    let capabilities = surface.get_capabilities(&adapter);
+    let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+        | (capabilities.usages & wgpu::TextureUsages::COPY_DST);
+
     let mut config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        format: surface.get_capabilities(&adapter).formats[0],
+        usage,
+        format: capabilities.formats[0],
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Fifo,
@@ -165,20 +198,17 @@ pub fn rui(view: impl View) {
         *GLOBAL_EVENT_LOOP_PROXY.lock().unwrap() = Some(event_loop.create_proxy());
     }
 
-    let mut vger = Vger::new(device.clone(), queue.clone(), config.format);
-    let mut cx = Context::new();
-    let mut mouse_position = LocalPoint::zero();
-
-    let mut commands: Vec<CommandInfo> = Vec::new();
-    let mut command_map = HashMap::new();
-    cx.commands(&view, &mut commands);
+    #[cfg(target_arch = "wasm32")]
+    EVENT_LOOP_PROXY.with(|proxy| *proxy.borrow_mut() = Some(event_loop.create_proxy()));
 
-    {
-        // So we can infer a type for CommandMap when winit is enabled.
-        command_map.insert("", "");
-    }
+    let mut host = RuiHost::new(view, device.clone(), queue.clone(), config.format);
 
-    let mut access_nodes = vec![];
+    // Spawned futures wake the event loop to be polled.
+    host.cx.set_wake(wake_event_loop);
+    let mut mouse_position = LocalPoint::zero();
+    let mut mouse_down = false;
+    let mut touch_tracker = TouchTracker::default();
+    let mut ime_rect = None;
 
     event_loop.run(move |event, _, control_flow| {
         // ControlFlow::Poll continuously runs the event loop, even if the OS hasn't
@@ -188,7 +218,15 @@ pub fn rui(view: impl View) {
         // ControlFlow::Wait pauses the event loop if no events are available to process.
         // This is ideal for non-game applications that only update in response to user
         // input, and uses significantly less power/CPU time than ControlFlow::Poll.
-        *control_flow = ControlFlow::Wait;
+        // Wake up again for the next animation frame or timer.
+        let mut timeout = host.next_timer();
+        if host.animating() {
+            timeout = Some(timeout.map_or(FRAME_INTERVAL, |t| t.min(FRAME_INTERVAL)));
+        }
+        match timeout {
+            Some(timeout) => control_flow.set_wait_timeout(timeout),
+            None => *control_flow = ControlFlow::Wait,
+        }
 
         match event {
             WEvent::WindowEvent {
@@ -220,7 +258,7 @@ pub fn rui(view: impl View) {
                 #[cfg(not(target_arch = "wasm32"))]
                 {
                     while let Some(f) = GLOBAL_WORK_QUEUE.lock().unwrap().pop_front() {
-                        f(&mut cx);
+                        f(&mut host.cx);
                     }
                 }
             }
@@ -239,13 +277,17 @@ pub fn rui(view: impl View) {
                 let width = window_size.width as f32 / scale;
                 let height = window_size.height as f32 / scale;
 
-                if cx.update(&view, &mut vger, &mut access_nodes, [width, height].into()) {
+                if host.update([width, height], scale) {
                     window.request_redraw();
                 }
 
-                if cx.window_title != window_title {
-                    window_title = cx.window_title.clone();
-                    window.set_title(&cx.window_title);
+                if let Some(commands) = host.updated_commands() {
+                    on_commands(&commands);
+                }
+
+                if host.window_title() != window_title {
+                    window_title = host.window_title().into();
+                    window.set_title(&window_title);
                 }
             }
             WEvent::RedrawRequested(_) => {
@@ -255,25 +297,29 @@ pub fn rui(view: impl View) {
                 // this event rather than in MainEventsCleared, since rendering in here allows
                 // the program to gracefully handle redraws requested by the OS.
 
-                let window_size = window.inner_size();
-                let scale = window.scale_factor() as f32;
-                // println!("window_size: {:?}", window_size);
-                let width = window_size.width as f32 / scale;
-                let height = window_size.height as f32 / scale;
-
                 // println!("RedrawRequested");
-                cx.render(
-                    RenderInfo {
-                        device: &device,
-                        surface: &surface,
-                        config: &config,
-                        queue: &queue,
-                    },
-                    &view,
-                    &mut vger,
-                    [width, height].into(),
-                    scale,
-                );
+                host.render(RenderInfo {
+                    device: &device,
+                    surface: &surface,
+                    config: &config,
+                    queue: &queue,
+                });
+
+                // The caret is found while drawing.
+                if host.ime_rect() != ime_rect {
+                    if ime_rect.is_none() || host.ime_rect().is_none() {
+                        window.set_ime_allowed(host.ime_rect().is_some());
+                    }
+                    ime_rect = host.ime_rect();
+                    if let Some(rect) = ime_rect {
+                        // Flip y coordinate, and put candidates below the caret.
+                        let height = window.inner_size().height as f64 / window.scale_factor();
+                        window.set_ime_position(LogicalPosition::new(
+                            rect.min_x() as f64,
+                            height - rect.min_y() as f64,
+                        ));
+                    }
+                }
             }
             WEvent::WindowEvent {
                 event: WindowEvent::MouseInput { state, button, .. },
@@ -281,25 +327,27 @@ pub fn rui(view: impl View) {
             } => {
                 match state {
                     ElementState::Pressed => {
-                        cx.mouse_button = match button {
+                        mouse_down = true;
+                        host.set_mouse_button(match button {
                             WMouseButton::Left => Some(MouseButton::Left),
                             WMouseButton::Right => Some(MouseButton::Right),
                             WMouseButton::Middle => Some(MouseButton::Center),
                             _ => None,
-                        };
+                        });
                         let event = Event::TouchBegin {
                             id: 0,
                             position: mouse_position,
                         };
-                        process_event(&mut cx, &view, &event, &window)
+                        process_event(&mut host, event, &window)
                     }
                     ElementState::Released => {
-                        cx.mouse_button = None;
+                        mouse_down = false;
+                        host.set_mouse_button(None);
                         let event = Event::TouchEnd {
                             id: 0,
                             position: mouse_position,
                         };
-                        process_event(&mut cx, &view, &event, &window)
+                        process_event(&mut host, event, &window)
                     }
                 };
             }
@@ -307,7 +355,10 @@ pub fn rui(view: impl View) {
                 window_id,
                 event:
                     WindowEvent::Touch(Touch {
-                        phase, location, ..
+                        phase,
+                        location,
+                        id,
+                        ..
                     }),
                 ..
             } => {
@@ -323,23 +374,14 @@ pub fn rui(view: impl View) {
                 ]
                 .into();
 
-                let delta = position - cx.previous_position[0];
-
-                // TODO: Multi-Touch management
                 let event = match phase {
-                    TouchPhase::Started => Some(Event::TouchBegin { id: 0, position }),
-                    TouchPhase::Moved => Some(Event::TouchMove {
-                        id: 0,
-                        position,
-                        delta,
-                    }),
-                    TouchPhase::Ended | TouchPhase::Cancelled => {
-                        Some(Event::TouchEnd { id: 0, position })
-                    }
+                    TouchPhase::Started => touch_tracker.begin(id, position),
+                    TouchPhase::Moved => touch_tracker.moved(id, position),
+                    TouchPhase::Ended | TouchPhase::Cancelled => touch_tracker.end(id, position),
                 };
 
                 if let Some(event) = event {
-                    process_event(&mut cx, &view, &event, &window);
+                    process_event(&mut host, event, &window);
                 }
             }
             WEvent::WindowEvent {
@@ -352,12 +394,50 @@ pub fn rui(view: impl View) {
                     (config.height as f32 - position.y as f32) / scale,
                 ]
                 .into();
-                // let event = Event::TouchMove {
-                //     id: 0,
-                //     position: mouse_position,
-                // };
-                // process_event(&mut cx, &view, &event, &window)
+                let event = Event::PointerMove {
+                    position: mouse_position,
+                };
+                process_event(&mut host, event, &window)
+            }
+            WEvent::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, phase, .. },
+                ..
+            } => {
+                // winit deltas move content right and down. Ours have y up.
+                let (delta, precise) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (
+                        LocalOffset::new(x * SCROLL_LINE_HEIGHT, -y * SCROLL_LINE_HEIGHT),
+                        false,
+                    ),
+                    MouseScrollDelta::PixelDelta(p) => {
+                        let scale = window.scale_factor() as f32;
+                        (
+                            LocalOffset::new(p.x as f32 / scale, -p.y as f32 / scale),
+                            true,
+                        )
+                    }
+                };
+                let phase = match phase {
+                    TouchPhase::Started => GestureState::Began,
+                    TouchPhase::Moved => GestureState::Changed,
+                    TouchPhase::Ended | TouchPhase::Cancelled => GestureState::Ended,
+                };
+                let event = Event::Scroll {
+                    position: mouse_position,
+                    delta,
+                    phase,
+                    precise,
+                };
+                process_event(&mut host, event, &window)
             }
+            WEvent::WindowEvent {
+                event: WindowEvent::CursorEntered { .. },
+                ..
+            } => process_event(&mut host, Event::PointerEnter, &window),
+            WEvent::WindowEvent {
+                event: WindowEvent::CursorLeft { .. },
+                ..
+            } => process_event(&mut host, Event::PointerLeave, &window),
 
             WEvent::WindowEvent {
                 event: WindowEvent::KeyboardInput { input, .. },
@@ -365,6 +445,7 @@ pub fn rui(view: impl View) {
             } => {
                 if input.state == ElementState::Pressed {
                     if let Some(code) = input.virtual_keycode {
+                        let cx = &host.cx;
                         let key = match code {
                             // VirtualKeyCode::Character(c) => Some(Key::Character(c)),
                             VirtualKeyCode::Key1 => {
@@ -487,9 +568,10 @@ pub fn rui(view: impl View) {
                             VirtualKeyCode::Comma => {
                                 Some(Key::Character(if cx.key_mods.shift { '<' } else { ',' }))
                             }
-                            VirtualKeyCode::Equals | VirtualKeyCode::NumpadEquals => {
-                                Some(Key::Character('='))
+                            VirtualKeyCode::Equals => {
+                                Some(Key::Character(if cx.key_mods.shift { '+' } else { '=' }))
                             }
+                            VirtualKeyCode::NumpadEquals => Some(Key::Character('=')),
                             VirtualKeyCode::Plus | VirtualKeyCode::NumpadAdd => {
                                 Some(Key::Character('+'))
                             }
@@ -502,6 +584,18 @@ pub fn rui(view: impl View) {
                             VirtualKeyCode::Grave => {
                                 Some(Key::Character(if cx.key_mods.shift { '~' } else { '`' }))
                             }
+                            VirtualKeyCode::LBracket => {
+                                Some(Key::Character(if cx.key_mods.shift { '{' } else { '[' }))
+                            }
+                            VirtualKeyCode::RBracket => {
+                                Some(Key::Character(if cx.key_mods.shift { '}' } else { ']' }))
+                            }
+                            VirtualKeyCode::Backslash => {
+                                Some(Key::Character(if cx.key_mods.shift { '|' } else { '\\' }))
+                            }
+                            VirtualKeyCode::Apostrophe => {
+                                Some(Key::Character(if cx.key_mods.shift { '"' } else { '\'' }))
+                            }
                             VirtualKeyCode::Return => Some(Key::Enter),
                             VirtualKeyCode::Tab => Some(Key::Tab),
                             VirtualKeyCode::Space => Some(Key::Space),
@@ -532,17 +626,43 @@ pub fn rui(view: impl View) {
                         };
 
                         if let Some(key) = key {
-                            cx.process(&view, &Event::Key(key))
+                            host.handle_event(Event::Key(key))
                         }
                     }
                 }
             }
 
+            WEvent::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c),
+                ..
+            } => {
+                // Control characters come as key presses, and shortcuts
+                // aren't text.
+                if !c.is_control() && !host.cx.key_mods.shortcut() {
+                    host.handle_event(Event::TextInput(c.into()))
+                }
+            }
+
+            WEvent::WindowEvent {
+                event: WindowEvent::Ime(ime),
+                ..
+            } => match ime {
+                Ime::Preedit(preedit, cursor) => {
+                    host.handle_event(Event::ImeComposition { preedit, cursor })
+                }
+                Ime::Commit(text) => host.handle_event(Event::TextInput(text)),
+                Ime::Disabled => host.handle_event(Event::ImeComposition {
+                    preedit: String::new(),
+                    cursor: None,
+                }),
+                Ime::Enabled => (),
+            },
+
             WEvent::WindowEvent {
                 event: WindowEvent::ModifiersChanged(mods),
                 ..
             } => {
-                cx.key_mods = KeyboardModifiers {
+                host.cx.key_mods = KeyboardModifiers {
                     shift: mods.shift(),
                     control: mods.ctrl(),
                     alt: mods.alt(),
@@ -554,6 +674,12 @@ pub fn rui(view: impl View) {
                 event: winit::event::DeviceEvent::MouseMotion { delta },
                 ..
             } => {
+                // Raw motion is only used for dragging, which keeps working
+                // while the cursor is grabbed. Hovering uses PointerMove.
+                if !mouse_down {
+                    return;
+                }
+
                 // Flip y coordinate.
                 let d: LocalOffset = [delta.0 as f32, -delta.1 as f32].into();
 
@@ -563,7 +689,7 @@ pub fn rui(view: impl View) {
                     delta: d,
                 };
 
-                process_event(&mut cx, &view, &event, &window);
+                process_event(&mut host, event, &window);
             }
             _ => (),
         }
@@ -593,6 +719,11 @@ pub trait Run: View + Sized {
     fn run(self) {
         rui(self)
     }
+
+    /// Runs the UI, calling `on_commands` when the menu commands change.
+    fn run_with_commands(self, on_commands: impl FnMut(&[CommandInfo]) + 'static) {
+        rui_with_commands(self, on_commands)
+    }
 }
 
 impl<V: View> Run for V {}